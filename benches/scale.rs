@@ -0,0 +1,98 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use vapours::{
+  frame::{PlaneView, PlaneViewMut},
+  scale::{scale_plane, scale_value, ScaleParams},
+  vs_enums::{GRAY16, GRAY8, GRAYS},
+};
+
+const WIDTH: usize = 1920;
+const HEIGHT: usize = 1080;
+
+const fn plane_view<T>(data: &[T]) -> PlaneView<'_, T> {
+  PlaneView {
+    data,
+    height: HEIGHT as i32,
+    width: WIDTH as i32,
+    stride: (WIDTH * size_of::<T>()) as isize,
+  }
+}
+
+const fn plane_view_mut<T>(data: &mut [T]) -> PlaneViewMut<'_, T> {
+  PlaneViewMut {
+    data,
+    height: HEIGHT as i32,
+    width: WIDTH as i32,
+    stride: (WIDTH * size_of::<T>()) as isize,
+  }
+}
+
+fn bench_16_to_8(c: &mut Criterion) {
+  let src: Vec<u16> = (0..WIDTH * HEIGHT).map(|i| (i * 31) as u16).collect();
+  let mut dst = vec![0u8; WIDTH * HEIGHT];
+
+  let params = ScaleParams::builder(&GRAY16, &GRAY8).build();
+
+  // Parity with `scale_value`.
+  scale_plane(&plane_view(&src), &mut plane_view_mut(&mut dst), &params);
+  for (&input, &output) in src.iter().zip(&dst) {
+    let expected = scale_value(input, &GRAY16, &GRAY8, None, None, None, None);
+    assert_eq!(f32::from(output).to_bits(), expected.to_bits());
+  }
+
+  let mut group = c.benchmark_group("16 to 8 bits");
+  group.bench_function("scale_value", |b| {
+    b.iter(|| {
+      for (&input, output) in src.iter().zip(dst.iter_mut()) {
+        *output = scale_value(black_box(input), &GRAY16, &GRAY8, None, None, None, None) as u8;
+      }
+    });
+  });
+  group.bench_function("scale_plane", |b| {
+    b.iter(|| {
+      scale_plane(
+        &plane_view(black_box(&src)),
+        &mut plane_view_mut(&mut dst),
+        &params,
+      );
+    });
+  });
+  group.finish();
+}
+
+fn bench_8_to_float(c: &mut Criterion) {
+  let src: Vec<u8> = (0..WIDTH * HEIGHT).map(|i| i as u8).collect();
+  let mut dst = vec![0f32; WIDTH * HEIGHT];
+
+  let params = ScaleParams::builder(&GRAY8, &GRAYS).build();
+
+  // Parity with `scale_value`.
+  scale_plane(&plane_view(&src), &mut plane_view_mut(&mut dst), &params);
+  for (&input, &output) in src.iter().zip(&dst) {
+    let expected = scale_value(input, &GRAY8, &GRAYS, None, None, None, None);
+    assert_eq!(output.to_bits(), expected.to_bits());
+  }
+
+  let mut group = c.benchmark_group("8 bits to float");
+  group.bench_function("scale_value", |b| {
+    b.iter(|| {
+      for (&input, output) in src.iter().zip(dst.iter_mut()) {
+        *output = scale_value(black_box(input), &GRAY8, &GRAYS, None, None, None, None);
+      }
+    });
+  });
+  group.bench_function("scale_plane", |b| {
+    b.iter(|| {
+      scale_plane(
+        &plane_view(black_box(&src)),
+        &mut plane_view_mut(&mut dst),
+        &params,
+      );
+    });
+  });
+  group.finish();
+}
+
+criterion_group!(benches, bench_16_to_8, bench_8_to_float);
+criterion_main!(benches);
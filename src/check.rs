@@ -0,0 +1,323 @@
+//! Clip validation.
+//!
+//! [`CheckClip`] performs the checks that filters usually do on their input
+//! clips when they are created. Failures are reported as
+//! [`VapoursError::InvalidClipError`] with the name of the filter, the name of
+//! the offending clip and the reason:
+//!
+//! ```
+//! # use vapours::{check::CheckClip, errors::VapoursError};
+//! # use vapoursynth4_rs::{node::VideoNode, ColorFamily};
+//! #
+//! # fn create(node: &VideoNode) -> Result<(), VapoursError> {
+//! node.check_constant_format("Invert", "clip")?;
+//! node.check_color_family("Invert", "clip", &[ColorFamily::Gray, ColorFamily::YUV])?;
+//! node.check_depth("Invert", "clip", &[8, 16])?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::num::NonZeroU32;
+
+use vapoursynth4_rs::{node::VideoNode, ColorFamily, VideoInfo};
+
+use crate::{
+  errors::{ClipErrorReason, VapoursError},
+  vs_enums::FormatName,
+};
+
+/// Checks on the properties of a clip.
+pub trait CheckClip {
+  /// Get the video info of the clip.
+  #[must_use]
+  fn video_info(&self) -> &VideoInfo;
+
+  /// Checks that the format of the clip is constant.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the format can vary between frames.
+  fn check_constant_format(&self, func: &str, clip: &str) -> Result<(), VapoursError> {
+    if self.video_info().format.color_family == ColorFamily::Undefined {
+      return Err(clip_error(func, clip, ClipErrorReason::VariableFormat));
+    }
+
+    Ok(())
+  }
+
+  /// Checks that the resolution of the clip is constant.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the resolution can vary between frames.
+  fn check_constant_resolution(&self, func: &str, clip: &str) -> Result<(), VapoursError> {
+    let info = self.video_info();
+    if info.width == 0 || info.height == 0 {
+      return Err(clip_error(func, clip, ClipErrorReason::VariableResolution));
+    }
+
+    Ok(())
+  }
+
+  /// Checks that both the format and the resolution of the clip are constant,
+  /// like vs-tools' `check_variable`.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the format or the resolution can vary between
+  /// frames.
+  fn check_constant(&self, func: &str, clip: &str) -> Result<(), VapoursError> {
+    self.check_constant_format(func, clip)?;
+    self.check_constant_resolution(func, clip)
+  }
+
+  /// Checks that the clip has the same constant format as `other`, which is
+  /// named `other_clip`.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error naming the offending clip if either format can vary
+  /// between frames, or if the formats differ.
+  fn check_same_format<T: CheckClip + ?Sized>(
+    &self,
+    func: &str,
+    clip: &str,
+    other: &T,
+    other_clip: &str,
+  ) -> Result<(), VapoursError> {
+    self.check_constant_format(func, clip)?;
+    other.check_constant_format(func, other_clip)?;
+
+    let format = &self.video_info().format;
+    let other_format = &other.video_info().format;
+    if format != other_format {
+      return Err(clip_error(
+        func,
+        clip,
+        ClipErrorReason::FormatMismatch(
+          FormatName(format.clone()).to_string(),
+          FormatName(other_format.clone()).to_string(),
+          other_clip.to_owned(),
+        ),
+      ));
+    }
+
+    Ok(())
+  }
+
+  /// Checks that the clip has a constant format of one of the `allowed` color
+  /// families.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the format can vary between frames or has another
+  /// color family.
+  fn check_color_family(
+    &self,
+    func: &str,
+    clip: &str,
+    allowed: &[ColorFamily],
+  ) -> Result<(), VapoursError> {
+    self.check_constant_format(func, clip)?;
+
+    let color_family = self.video_info().format.color_family;
+    if !allowed.contains(&color_family) {
+      return Err(clip_error(
+        func,
+        clip,
+        ClipErrorReason::UnsupportedColorFamily(color_family, allowed.to_vec()),
+      ));
+    }
+
+    Ok(())
+  }
+
+  /// Checks that the clip has a constant format of one of the `allowed` bit
+  /// depths.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the format can vary between frames or has another
+  /// bit depth.
+  fn check_depth(&self, func: &str, clip: &str, allowed: &[i32]) -> Result<(), VapoursError> {
+    self.check_constant_format(func, clip)?;
+
+    let depth = self.video_info().format.bits_per_sample;
+    if !allowed.contains(&depth) {
+      return Err(clip_error(
+        func,
+        clip,
+        ClipErrorReason::UnsupportedDepth(depth, allowed.to_vec()),
+      ));
+    }
+
+    Ok(())
+  }
+
+  /// Checks that the clip has a constant resolution whose width and height
+  /// are multiples of `modulus`.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the resolution can vary between frames or is not
+  /// mod `modulus`.
+  fn check_mod(&self, func: &str, clip: &str, modulus: NonZeroU32) -> Result<(), VapoursError> {
+    self.check_constant_resolution(func, clip)?;
+
+    // A constant resolution is positive.
+    let info = self.video_info();
+    if info.width as u32 % modulus != 0 || info.height as u32 % modulus != 0 {
+      return Err(clip_error(
+        func,
+        clip,
+        ClipErrorReason::InvalidResolution(info.width, info.height, modulus.get()),
+      ));
+    }
+
+    Ok(())
+  }
+}
+
+impl CheckClip for VideoInfo {
+  fn video_info(&self) -> &VideoInfo {
+    self
+  }
+}
+
+impl CheckClip for VideoNode {
+  fn video_info(&self) -> &VideoInfo {
+    self.info()
+  }
+}
+
+fn clip_error(func: &str, clip: &str, reason: ClipErrorReason) -> VapoursError {
+  VapoursError::InvalidClipError(func.to_owned(), clip.to_owned(), reason)
+}
+
+#[cfg(test)]
+mod tests {
+  use vapoursynth4_rs::{frame::VideoFormat, SampleType};
+
+  use crate::vs_enums::{make_video_format, GRAY8, RGBS, YUV420P10, YUV420P8};
+
+  use super::*;
+
+  const VARIABLE_FORMAT: VideoFormat =
+    make_video_format(ColorFamily::Undefined, SampleType::Integer, 0, 0, 0);
+
+  const fn video_info(format: VideoFormat, width: i32, height: i32) -> VideoInfo {
+    VideoInfo {
+      format,
+      fps_num: 24000,
+      fps_den: 1001,
+      width,
+      height,
+      num_frames: 100,
+    }
+  }
+
+  fn reason(result: Result<(), VapoursError>) -> ClipErrorReason {
+    match result {
+      Err(VapoursError::InvalidClipError(func, clip, reason)) => {
+        assert_eq!(func, "Filter");
+        assert_eq!(clip, "clip");
+        reason
+      }
+      result => panic!("expected an invalid clip error, got {result:?}"),
+    }
+  }
+
+  #[test]
+  fn test_check_constant() {
+    let info = video_info(YUV420P8, 1920, 1080);
+    assert!(info.check_constant("Filter", "clip").is_ok());
+
+    let variable_format = video_info(VARIABLE_FORMAT, 1920, 1080);
+    assert_eq!(
+      reason(variable_format.check_constant("Filter", "clip")),
+      ClipErrorReason::VariableFormat
+    );
+
+    let variable_resolution = video_info(YUV420P8, 0, 0);
+    assert!(variable_resolution
+      .check_constant_format("Filter", "clip")
+      .is_ok());
+    assert_eq!(
+      reason(variable_resolution.check_constant("Filter", "clip")),
+      ClipErrorReason::VariableResolution
+    );
+  }
+
+  #[test]
+  fn test_check_same_format() {
+    let info = video_info(YUV420P8, 1920, 1080);
+    let other = video_info(YUV420P8, 1280, 720);
+    assert!(info
+      .check_same_format("Filter", "clip", &other, "ref")
+      .is_ok());
+
+    let other = video_info(YUV420P10, 1920, 1080);
+    assert_eq!(
+      reason(info.check_same_format("Filter", "clip", &other, "ref")),
+      ClipErrorReason::FormatMismatch("YUV420P8".into(), "YUV420P10".into(), "ref".into())
+    );
+
+    let other = video_info(VARIABLE_FORMAT, 1920, 1080);
+    assert!(matches!(
+      info.check_same_format("Filter", "clip", &other, "ref"),
+      Err(VapoursError::InvalidClipError(_, clip, ClipErrorReason::VariableFormat)) if clip == "ref"
+    ));
+  }
+
+  #[test]
+  fn test_check_color_family_and_depth() {
+    let info = video_info(RGBS, 1920, 1080);
+    assert!(info
+      .check_color_family("Filter", "clip", &[ColorFamily::RGB, ColorFamily::YUV])
+      .is_ok());
+    assert_eq!(
+      reason(info.check_color_family("Filter", "clip", &[ColorFamily::Gray])),
+      ClipErrorReason::UnsupportedColorFamily(ColorFamily::RGB, vec![ColorFamily::Gray])
+    );
+
+    assert!(info.check_depth("Filter", "clip", &[16, 32]).is_ok());
+    let info = video_info(GRAY8, 1920, 1080);
+    assert_eq!(
+      reason(info.check_depth("Filter", "clip", &[16, 32])),
+      ClipErrorReason::UnsupportedDepth(8, vec![16, 32])
+    );
+  }
+
+  #[test]
+  fn test_check_mod() {
+    let modulus = |modulus| NonZeroU32::new(modulus).unwrap();
+
+    let info = video_info(YUV420P8, 1920, 1080);
+    assert!(info.check_mod("Filter", "clip", modulus(1)).is_ok());
+    assert!(info.check_mod("Filter", "clip", modulus(4)).is_ok());
+    assert_eq!(
+      reason(info.check_mod("Filter", "clip", modulus(16))),
+      ClipErrorReason::InvalidResolution(1920, 1080, 16)
+    );
+
+    let info = video_info(YUV420P8, 0, 0);
+    assert_eq!(
+      reason(info.check_mod("Filter", "clip", modulus(2))),
+      ClipErrorReason::VariableResolution
+    );
+  }
+
+  #[test]
+  fn test_invalid_clip_error_message() {
+    let info = video_info(YUV420P8, 1920, 1080);
+    let other = video_info(GRAY8, 1920, 1080);
+    assert_eq!(
+      info
+        .check_same_format("Filter", "clip", &other, "ref")
+        .unwrap_err()
+        .to_string(),
+      "Clip 'clip' passed to 'Filter' is not supported: format YUV420P8 does not match format \
+       Gray8 of clip 'ref'."
+    );
+  }
+}
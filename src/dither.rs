@@ -0,0 +1,1664 @@
+//! Native dithering.
+//!
+//! The functions in this module convert a plane or a whole frame to another
+//! format, usually a lower integer bit depth, without relying on fmtconv or
+//! zimg. Values are scaled with the same range and offset semantics as
+//! [`scale_value`](crate::scale::scale_value), described by a
+//! [`DepthConversion`].
+
+use std::{
+  collections::HashMap,
+  iter::zip,
+  marker::PhantomData,
+  sync::{Arc, Mutex, OnceLock, PoisonError},
+};
+
+use vapoursynth4_rs::{
+  frame::{VideoFormat, VideoFrame},
+  ColorFamily,
+};
+
+use crate::{
+  enums::ColorRange,
+  errors::VapoursError,
+  frame::{dispatch, zip_rows, Pixel, PixelVisitor, PlaneView, PlaneViewMut, VapoursVideoFrame},
+  generic::HoldsVideoFormat,
+  scale::ScaleParams,
+  utils::DitherType,
+};
+
+/// Backend name reported for dither types without a native implementation.
+const NATIVE_BACKEND: &str = "vapours";
+
+/// How pixel values are converted from one format to another: the transform
+/// of a [`ScaleParams`], with the rounding left to the dither.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DepthConversion(ScaleParams);
+
+impl DepthConversion {
+  /// Creates the conversion between two formats. Ranges and `chroma` default
+  /// as in [`scale_value`](crate::scale::scale_value).
+  #[must_use]
+  pub fn new<U, V>(
+    format_in: &U,
+    format_out: &V,
+    range_in: Option<ColorRange>,
+    range_out: Option<ColorRange>,
+    chroma: Option<bool>,
+  ) -> Self
+  where
+    U: HoldsVideoFormat,
+    V: HoldsVideoFormat,
+  {
+    let mut params = ScaleParams::builder(format_in, format_out);
+    if let Some(range_in) = range_in {
+      params = params.range_in(range_in);
+    }
+    if let Some(range_out) = range_out {
+      params = params.range_out(range_out);
+    }
+    if let Some(chroma) = chroma {
+      params = params.chroma(chroma);
+    }
+    Self(params.build())
+  }
+
+  /// Scales a value to the output format, without rounding.
+  #[inline]
+  #[must_use]
+  pub fn scale(&self, value: f32) -> f32 {
+    self.0.apply_unrounded(value)
+  }
+
+  /// Quantizes a scaled value to the output format. `threshold` lies in
+  /// `[0, 1)` and decides how the value is rounded: `0.5` rounds to nearest.
+  /// Float output is returned unchanged.
+  #[inline]
+  #[must_use]
+  pub fn quantize(&self, value: f32, threshold: f32) -> f32 {
+    self
+      .0
+      .integer_peak_out()
+      .map_or(value, |peak| (value + threshold).floor().clamp(0.0, peak))
+  }
+
+  /// Whether the output format is an integer format.
+  #[must_use]
+  pub const fn is_integer_out(&self) -> bool {
+    self.0.integer_peak_out().is_some()
+  }
+}
+
+impl From<ScaleParams> for DepthConversion {
+  fn from(params: ScaleParams) -> Self {
+    Self(params)
+  }
+}
+
+/// A square Bayer threshold matrix with a power of two size.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BayerMatrix {
+  size_log2: u32,
+  values: Vec<u32>,
+}
+
+impl BayerMatrix {
+  /// Largest supported `size_log2`, i.e. a 256x256 matrix.
+  pub const MAX_SIZE_LOG2: u32 = 8;
+
+  /// Creates a Bayer matrix of `2^size_log2` by `2^size_log2` elements.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `size_log2` is greater than [`BayerMatrix::MAX_SIZE_LOG2`].
+  #[must_use]
+  pub fn new(size_log2: u32) -> Self {
+    assert!(
+      size_log2 <= Self::MAX_SIZE_LOG2,
+      "Bayer matrix size must be at most 2^{}",
+      Self::MAX_SIZE_LOG2
+    );
+
+    // Each step builds a matrix of twice the size from four copies of the
+    // previous one: [[4M, 4M + 2], [4M + 3, 4M + 1]].
+    let mut values = vec![0];
+    for step in 0..size_log2 {
+      let size = 1 << step;
+      let mut next = vec![0; size * size * 4];
+      for y in 0..size {
+        for x in 0..size {
+          let value = values[y * size + x] * 4;
+          next[y * size * 2 + x] = value;
+          next[y * size * 2 + x + size] = value + 2;
+          next[(y + size) * size * 2 + x] = value + 3;
+          next[(y + size) * size * 2 + x + size] = value + 1;
+        }
+      }
+      values = next;
+    }
+
+    Self { size_log2, values }
+  }
+
+  /// Width and height of the matrix.
+  #[must_use]
+  pub const fn size(&self) -> usize {
+    1 << self.size_log2
+  }
+
+  /// Returns the raw matrix value at `(x, y)`, wrapping around the matrix.
+  #[must_use]
+  pub fn value(&self, x: usize, y: usize) -> u32 {
+    let mask = self.size() - 1;
+    self.values[(y & mask) * self.size() + (x & mask)]
+  }
+}
+
+impl ThresholdMap for BayerMatrix {
+  /// Wraps around the matrix.
+  #[allow(clippy::cast_precision_loss)]
+  fn threshold(&self, x: usize, y: usize) -> f32 {
+    (self.value(x, y) as f32 + 0.5) / self.values.len() as f32
+  }
+}
+
+impl Default for BayerMatrix {
+  /// An 8x8 matrix.
+  fn default() -> Self {
+    Self::new(3)
+  }
+}
+
+/// A square blue-noise threshold matrix with a power of two size, generated
+/// with Ulichney's void-and-cluster method.
+///
+/// Generation is quadratic in the number of elements, so prefer
+/// [`VoidClusterMatrix::cached`] over [`VoidClusterMatrix::new`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VoidClusterMatrix {
+  size_log2: u32,
+  values: Vec<u32>,
+}
+
+impl VoidClusterMatrix {
+  /// Largest supported `size_log2`, i.e. a 128x128 matrix.
+  pub const MAX_SIZE_LOG2: u32 = 7;
+
+  /// Standard deviation of the Gaussian filter measuring clusters and voids.
+  const SIGMA: f64 = 1.5;
+
+  /// Seed of the initial random pattern.
+  const SEED: u64 = 0x5eed;
+
+  /// Generates a matrix of `2^size_log2` by `2^size_log2` elements.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `size_log2` is greater than [`VoidClusterMatrix::MAX_SIZE_LOG2`].
+  #[must_use]
+  pub fn new(size_log2: u32) -> Self {
+    assert!(
+      size_log2 <= Self::MAX_SIZE_LOG2,
+      "void-and-cluster matrix size must be at most 2^{}",
+      Self::MAX_SIZE_LOG2
+    );
+
+    let size = 1 << size_log2;
+    let len = size * size;
+    let mut pattern = PatternEnergy::new(size, Self::SIGMA);
+
+    // Initial pattern: a tenth of the elements set at random.
+    let ones = (len / 10).max(1);
+    let mut rng = SplitMix64::new(Self::SEED);
+    while pattern.count < ones {
+      let index = (rng.next_u64() % len as u64) as usize;
+      if !pattern.bits[index] {
+        pattern.toggle(index);
+      }
+    }
+
+    // Move points from the tightest cluster to the largest void until that
+    // stops changing anything.
+    for _ in 0..len {
+      let cluster = pattern.tightest_cluster();
+      pattern.toggle(cluster);
+      let void = pattern.largest_void();
+      pattern.toggle(void);
+      if void == cluster {
+        break;
+      }
+    }
+
+    let mut values = vec![0; len];
+
+    // Ranks below the initial pattern: remove its tightest clusters first.
+    let mut removing = pattern.clone();
+    for rank in (0..ones).rev() {
+      let cluster = removing.tightest_cluster();
+      removing.toggle(cluster);
+      values[cluster] = rank as u32;
+    }
+
+    // Ranks above it: fill the largest voids first.
+    for rank in ones..len {
+      let void = pattern.largest_void();
+      pattern.toggle(void);
+      values[void] = rank as u32;
+    }
+
+    Self { size_log2, values }
+  }
+
+  /// Returns the matrix of `2^size_log2` by `2^size_log2` elements, generating
+  /// it on first use.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `size_log2` is greater than [`VoidClusterMatrix::MAX_SIZE_LOG2`].
+  #[must_use]
+  pub fn cached(size_log2: u32) -> Arc<Self> {
+    type Slot = Arc<OnceLock<Arc<VoidClusterMatrix>>>;
+    static CACHE: OnceLock<Mutex<HashMap<u32, Slot>>> = OnceLock::new();
+
+    assert!(
+      size_log2 <= Self::MAX_SIZE_LOG2,
+      "void-and-cluster matrix size must be at most 2^{}",
+      Self::MAX_SIZE_LOG2
+    );
+
+    // The lock only guards the map of slots. Each matrix is generated in its
+    // own slot, so generating one size does not block users of another.
+    let slot = Arc::clone(
+      CACHE
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(size_log2)
+        .or_default(),
+    );
+    Arc::clone(slot.get_or_init(|| Arc::new(Self::new(size_log2))))
+  }
+
+  /// Width and height of the matrix.
+  #[must_use]
+  pub const fn size(&self) -> usize {
+    1 << self.size_log2
+  }
+
+  /// Returns the rank of the element at `(x, y)`, wrapping around the matrix.
+  #[must_use]
+  pub fn value(&self, x: usize, y: usize) -> u32 {
+    let mask = self.size() - 1;
+    self.values[(y & mask) * self.size() + (x & mask)]
+  }
+}
+
+impl ThresholdMap for VoidClusterMatrix {
+  /// Wraps around the matrix.
+  #[allow(clippy::cast_precision_loss)]
+  fn threshold(&self, x: usize, y: usize) -> f32 {
+    (self.value(x, y) as f32 + 0.5) / self.values.len() as f32
+  }
+}
+
+/// A binary pattern on a torus and its energy, the Gaussian-filtered sum of its
+/// set elements.
+#[derive(Clone)]
+struct PatternEnergy {
+  size: usize,
+  filter: Vec<f64>,
+  bits: Vec<bool>,
+  energy: Vec<f64>,
+  count: usize,
+}
+
+impl PatternEnergy {
+  #[allow(clippy::cast_precision_loss)]
+  fn new(size: usize, sigma: f64) -> Self {
+    let mut filter = vec![0.0; size * size];
+    for dy in 0..size {
+      for dx in 0..size {
+        let distance_x = dx.min(size - dx) as f64;
+        let distance_y = dy.min(size - dy) as f64;
+        let distance = distance_x.mul_add(distance_x, distance_y * distance_y);
+        filter[dy * size + dx] = (-distance / (2.0 * sigma * sigma)).exp();
+      }
+    }
+
+    Self {
+      size,
+      filter,
+      bits: vec![false; size * size],
+      energy: vec![0.0; size * size],
+      count: 0,
+    }
+  }
+
+  /// Sets or clears an element, updating the energy of every element.
+  fn toggle(&mut self, index: usize) {
+    let set = !self.bits[index];
+    self.bits[index] = set;
+    if set {
+      self.count += 1;
+    } else {
+      self.count -= 1;
+    }
+
+    let sign = if set { 1.0 } else { -1.0 };
+    let (px, py) = (index % self.size, index / self.size);
+    for y in 0..self.size {
+      let dy = (y + self.size - py) % self.size;
+      for x in 0..self.size {
+        let dx = (x + self.size - px) % self.size;
+        self.energy[y * self.size + x] += sign * self.filter[dy * self.size + dx];
+      }
+    }
+  }
+
+  /// Set element with the highest energy, the first one on ties.
+  fn tightest_cluster(&self) -> usize {
+    self.extreme(true, |energy, best| energy > best)
+  }
+
+  /// Unset element with the lowest energy, the first one on ties.
+  fn largest_void(&self) -> usize {
+    self.extreme(false, |energy, best| energy < best)
+  }
+
+  fn extreme(&self, set: bool, better: impl Fn(f64, f64) -> bool) -> usize {
+    let mut best: Option<usize> = None;
+    for (index, (&bit, &energy)) in zip(&self.bits, &self.energy).enumerate() {
+      if bit == set && best.is_none_or(|best| better(energy, self.energy[best])) {
+        best = Some(index);
+      }
+    }
+    best.expect("pattern should have an element to pick")
+  }
+}
+
+/// Thresholds from the R2 quasirandom sequence, a low-discrepancy lattice
+/// based on the plastic number.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct R2Sequence;
+
+impl ThresholdMap for R2Sequence {
+  #[allow(clippy::cast_precision_loss)]
+  fn threshold(&self, x: usize, y: usize) -> f32 {
+    const PLASTIC: f64 = 1.324_717_957_244_746;
+    let value = (x as f64 / PLASTIC + y as f64 / (PLASTIC * PLASTIC)).fract();
+    // Stay below 1 when rounding to `f32`.
+    (value as f32).min(1.0 - f32::EPSILON / 2.0)
+  }
+}
+
+/// A small deterministic pseudo-random generator.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+  const GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+  const fn new(seed: u64) -> Self {
+    Self(seed)
+  }
+
+  const fn next_u64(&mut self) -> u64 {
+    self.0 = self.0.wrapping_add(Self::GAMMA);
+    Self::mix(self.0)
+  }
+
+  /// Returns the `index`-th output of a generator seeded with `seed`, without
+  /// stepping through the previous ones.
+  const fn nth(seed: u64, index: u64) -> u64 {
+    Self::mix(seed.wrapping_add(index.wrapping_add(1).wrapping_mul(Self::GAMMA)))
+  }
+
+  const fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+  }
+}
+
+/// Uniform white noise thresholds, a function of a seed, a frame number, a
+/// plane index and the pixel position only. The same frame is therefore
+/// dithered identically however and wherever it is rendered.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RandomNoise {
+  key: u64,
+}
+
+impl RandomNoise {
+  /// Creates the noise of one plane of one frame.
+  #[must_use]
+  pub const fn new(seed: u64, frame_number: i32, plane: i32) -> Self {
+    let frame_number = frame_number as u32 as u64;
+    let plane = plane as u32 as u64;
+    Self {
+      key: SplitMix64::nth(seed, (frame_number << 32) | plane),
+    }
+  }
+}
+
+impl ThresholdMap for RandomNoise {
+  #[allow(clippy::cast_precision_loss)]
+  fn threshold(&self, x: usize, y: usize) -> f32 {
+    let bits = SplitMix64::nth(self.key, ((y as u64) << 32) | x as u64) >> 40;
+    bits as f32 / (1 << 24) as f32
+  }
+}
+
+/// A map of thresholds for [`ordered_dither`].
+pub trait ThresholdMap {
+  /// Returns the threshold in `[0, 1)` at `(x, y)`.
+  fn threshold(&self, x: usize, y: usize) -> f32;
+}
+
+/// Converts a plane with ordered dithering, e.g. using a [`BayerMatrix`],
+/// [`VoidClusterMatrix`], [`R2Sequence`] or [`RandomNoise`].
+///
+/// # Errors
+///
+/// Returns [`VapoursError::UnsupportedFormatError`] if `src` and `dst` have
+/// different dimensions.
+pub fn ordered_dither<S, D, M>(
+  src: &PlaneView<'_, S>,
+  dst: &mut PlaneViewMut<'_, D>,
+  conversion: &DepthConversion,
+  matrix: &M,
+) -> Result<(), VapoursError>
+where
+  S: Pixel,
+  D: Pixel,
+  M: ThresholdMap + ?Sized,
+{
+  check_plane_dimensions(src, dst)?;
+
+  for (y, (src_row, dst_row)) in zip_rows(src, dst).enumerate() {
+    for (x, (src_pixel, dst_pixel)) in zip(src_row, dst_row).enumerate() {
+      let value = conversion.scale(src_pixel.to_f32());
+      *dst_pixel = D::from_f32(conversion.quantize(value, matrix.threshold(x, y)));
+    }
+  }
+
+  Ok(())
+}
+
+/// Returns an error unless `src` and `dst` have the same dimensions.
+fn check_plane_dimensions<S, D>(
+  src: &PlaneView<'_, S>,
+  dst: &PlaneViewMut<'_, D>,
+) -> Result<(), VapoursError> {
+  if (src.width, src.height) == (dst.width, dst.height) {
+    Ok(())
+  } else {
+    Err(VapoursError::UnsupportedFormatError(format!(
+      "cannot convert a {}x{} plane into a {}x{} plane",
+      src.width, src.height, dst.width, dst.height
+    )))
+  }
+}
+
+/// Options for [`dither_plane`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DitherOptions {
+  /// Threshold matrix used by [`DitherType::Ordered`].
+  pub bayer_matrix: BayerMatrix,
+
+  /// `size_log2` of the cached [`VoidClusterMatrix`] used by
+  /// [`DitherType::Void`].
+  pub void_size_log2: u32,
+
+  /// Seed of the [`RandomNoise`] used by [`DitherType::Random`].
+  pub seed: u64,
+
+  /// Frame number the plane belongs to, mixed into the seed.
+  pub frame_number: i32,
+
+  /// Index of the plane, mixed into the seed.
+  pub plane: i32,
+}
+
+impl Default for DitherOptions {
+  /// An 8x8 Bayer matrix, a 64x64 void-and-cluster matrix and noise for the
+  /// first plane of the first frame with a seed of 0.
+  fn default() -> Self {
+    Self {
+      bayer_matrix: BayerMatrix::default(),
+      void_size_log2: 6,
+      seed: 0,
+      frame_number: 0,
+      plane: 0,
+    }
+  }
+}
+
+/// Converts a plane with the given dither type.
+///
+/// [`DitherType::Auto`] behaves as [`DitherType::ErrorDiffusion`], which
+/// leaves values that need no rounding untouched.
+///
+/// # Errors
+///
+/// Returns [`VapoursError::UnsupportedDitherError`] if the dither type has no
+/// native implementation, or if it is [`DitherType::Void`] and
+/// [`DitherOptions::void_size_log2`] is greater than
+/// [`VoidClusterMatrix::MAX_SIZE_LOG2`]. Returns
+/// [`VapoursError::UnsupportedFormatError`] if `src` and `dst` have different
+/// dimensions.
+pub fn dither_plane<S, D>(
+  src: &PlaneView<'_, S>,
+  dst: &mut PlaneViewMut<'_, D>,
+  conversion: &DepthConversion,
+  dither_type: DitherType,
+  options: &DitherOptions,
+) -> Result<(), VapoursError>
+where
+  S: Pixel,
+  D: Pixel,
+{
+  check_plane_dimensions(src, dst)?;
+
+  match dither_type {
+    DitherType::None => {
+      for (src_row, dst_row) in zip_rows(src, dst) {
+        for (src_pixel, dst_pixel) in zip(src_row, dst_row) {
+          let value = conversion.scale(src_pixel.to_f32());
+          *dst_pixel = D::from_f32(conversion.quantize(value, 0.5));
+        }
+      }
+      Ok(())
+    }
+    DitherType::Ordered => ordered_dither(src, dst, conversion, &options.bayer_matrix),
+    DitherType::Void => {
+      if options.void_size_log2 > VoidClusterMatrix::MAX_SIZE_LOG2 {
+        return Err(VapoursError::UnsupportedDitherError(
+          dither_type,
+          format!(
+            "{NATIVE_BACKEND} with a 2^{} matrix",
+            options.void_size_log2
+          ),
+        ));
+      }
+      let matrix = VoidClusterMatrix::cached(options.void_size_log2);
+      ordered_dither(src, dst, conversion, matrix.as_ref())
+    }
+    DitherType::Quasirandom => ordered_dither(src, dst, conversion, &R2Sequence),
+    DitherType::Random => {
+      let noise = RandomNoise::new(options.seed, options.frame_number, options.plane);
+      ordered_dither(src, dst, conversion, &noise)
+    }
+    DitherType::Auto => error_diffusion(src, dst, conversion, DitherType::ErrorDiffusion),
+    _ => error_diffusion(src, dst, conversion, dither_type),
+  }
+}
+
+/// One neighbour receiving part of the quantization error.
+#[derive(Clone, Copy)]
+struct Tap {
+  /// Horizontal offset, mirrored on right-to-left lines.
+  dx: isize,
+
+  /// Vertical offset, at most 2.
+  dy: usize,
+
+  weight: f32,
+}
+
+const fn tap(dx: isize, dy: usize, weight: f32) -> Tap {
+  Tap { dx, dy, weight }
+}
+
+/// How the quantization error of a pixel is spread to its neighbours.
+enum Kernel {
+  /// Fixed weights, divided by `divisor`.
+  Fixed {
+    taps: &'static [Tap],
+    divisor: f32,
+    serpentine: bool,
+  },
+
+  /// Weights depending on the input intensity, with a serpentine scan.
+  Ostromoukhov,
+}
+
+impl Kernel {
+  const fn from_dither_type(dither_type: DitherType) -> Option<Self> {
+    const FLOYD_STEINBERG: &[Tap] = &[
+      tap(1, 0, 7.0),
+      tap(-1, 1, 3.0),
+      tap(0, 1, 5.0),
+      tap(1, 1, 1.0),
+    ];
+    const SIERRA_2_4A: &[Tap] = &[tap(1, 0, 2.0), tap(-1, 1, 1.0), tap(0, 1, 1.0)];
+    const STUCKI: &[Tap] = &[
+      tap(1, 0, 8.0),
+      tap(2, 0, 4.0),
+      tap(-2, 1, 2.0),
+      tap(-1, 1, 4.0),
+      tap(0, 1, 8.0),
+      tap(1, 1, 4.0),
+      tap(2, 1, 2.0),
+      tap(-2, 2, 1.0),
+      tap(-1, 2, 2.0),
+      tap(0, 2, 4.0),
+      tap(1, 2, 2.0),
+      tap(2, 2, 1.0),
+    ];
+    // Only 6/8 of the error is spread.
+    const ATKINSON: &[Tap] = &[
+      tap(1, 0, 1.0),
+      tap(2, 0, 1.0),
+      tap(-1, 1, 1.0),
+      tap(0, 1, 1.0),
+      tap(1, 1, 1.0),
+      tap(0, 2, 1.0),
+    ];
+
+    let (taps, divisor, serpentine) = match dither_type {
+      DitherType::ErrorDiffusion => (FLOYD_STEINBERG, 16.0, false),
+      DitherType::ErrorDiffusionFmtc => (FLOYD_STEINBERG, 16.0, true),
+      DitherType::Sierra24a => (SIERRA_2_4A, 4.0, false),
+      DitherType::Stucki => (STUCKI, 42.0, false),
+      DitherType::Atkinson => (ATKINSON, 8.0, false),
+      DitherType::Ostromoukhov => return Some(Self::Ostromoukhov),
+      _ => return None,
+    };
+
+    Some(Self::Fixed {
+      taps,
+      divisor,
+      serpentine,
+    })
+  }
+
+  const fn is_serpentine(&self) -> bool {
+    match self {
+      Self::Fixed { serpentine, .. } => *serpentine,
+      Self::Ostromoukhov => true,
+    }
+  }
+}
+
+/// Weights to the right, bottom left and bottom neighbours, and their sum,
+/// for input intensities 0 to 127 from "A Simple and Efficient Error-Diffusion
+/// Algorithm" (Ostromoukhov, 2001). Intensities 128 to 255 mirror them.
+#[rustfmt::skip]
+const OSTROMOUKHOV_COEFFICIENTS: [[u16; 4]; 128] = [
+  [13, 0, 5, 18], [13, 0, 5, 18], [21, 0, 10, 31], [7, 0, 4, 11],
+  [8, 0, 5, 13], [47, 3, 28, 78], [23, 3, 13, 39], [15, 3, 8, 26],
+  [22, 6, 11, 39], [43, 15, 20, 78], [7, 3, 3, 13], [501, 224, 211, 936],
+  [249, 116, 103, 468], [165, 80, 67, 312], [123, 62, 49, 234], [489, 256, 191, 936],
+  [81, 44, 31, 156], [483, 272, 181, 936], [60, 35, 22, 117], [53, 32, 19, 104],
+  [237, 148, 83, 468], [471, 304, 161, 936], [3, 2, 1, 6], [481, 314, 185, 980],
+  [354, 226, 155, 735], [1389, 866, 685, 2940], [227, 138, 125, 490], [267, 158, 163, 588],
+  [327, 188, 220, 735], [61, 34, 45, 140], [627, 338, 505, 1470], [1227, 638, 1075, 2940],
+  [20, 10, 19, 49], [1937, 1000, 1767, 4704], [977, 520, 855, 2352], [657, 360, 551, 1568],
+  [71, 40, 57, 168], [2005, 1160, 1539, 4704], [337, 200, 247, 784], [2039, 1240, 1425, 4704],
+  [257, 160, 171, 588], [691, 440, 437, 1568], [1045, 680, 627, 2352], [301, 200, 171, 672],
+  [177, 120, 95, 392], [2141, 1480, 1083, 4704], [1079, 760, 513, 2352], [725, 520, 323, 1568],
+  [137, 100, 57, 294], [2209, 1640, 855, 4704], [53, 40, 19, 112], [2243, 1720, 741, 4704],
+  [565, 440, 171, 1176], [759, 600, 209, 1568], [1147, 920, 285, 2352], [2311, 1880, 513, 4704],
+  [97, 80, 19, 196], [335, 280, 57, 672], [1181, 1000, 171, 2352], [793, 680, 95, 1568],
+  [599, 520, 57, 1176], [2413, 2120, 171, 4704], [405, 360, 19, 784], [2447, 2200, 57, 4704],
+  [11, 10, 0, 21], [158, 151, 3, 312], [178, 179, 7, 364], [1030, 1091, 63, 2184],
+  [248, 277, 21, 546], [318, 375, 35, 728], [458, 571, 63, 1092], [878, 1159, 147, 2184],
+  [5, 7, 1, 13], [172, 181, 37, 390], [97, 76, 22, 195], [72, 41, 17, 130],
+  [119, 47, 29, 195], [4, 1, 1, 6], [4, 1, 1, 6], [4, 1, 1, 6],
+  [4, 1, 1, 6], [4, 1, 1, 6], [4, 1, 1, 6], [4, 1, 1, 6],
+  [4, 1, 1, 6], [4, 1, 1, 6], [65, 18, 17, 100], [95, 29, 26, 150],
+  [185, 62, 53, 300], [30, 11, 9, 50], [35, 14, 11, 60], [85, 37, 28, 150],
+  [55, 26, 19, 100], [80, 41, 29, 150], [155, 86, 59, 300], [5, 3, 2, 10],
+  [5, 3, 2, 10], [5, 3, 2, 10], [5, 3, 2, 10], [5, 3, 2, 10],
+  [5, 3, 2, 10], [5, 3, 2, 10], [5, 3, 2, 10], [5, 3, 2, 10],
+  [5, 3, 2, 10], [5, 3, 2, 10], [5, 3, 2, 10], [5, 3, 2, 10],
+  [5, 3, 2, 10], [5, 3, 2, 10], [5, 3, 2, 10], [5, 3, 2, 10],
+  [5, 3, 2, 10], [5, 3, 2, 10], [5, 3, 2, 10], [5, 3, 2, 10],
+  [5, 3, 2, 10], [5, 3, 2, 10], [5, 3, 2, 10], [5, 3, 2, 10],
+  [5, 3, 2, 10], [5, 3, 2, 10], [5, 3, 2, 10], [5, 3, 2, 10],
+  [5, 3, 2, 10], [5, 3, 2, 10], [5, 3, 2, 10], [5, 3, 2, 10],
+];
+
+/// Returns the Ostromoukhov taps for a scaled value, indexed by its position
+/// between the two nearest output levels.
+fn ostromoukhov_taps(value: f32) -> [Tap; 3] {
+  let level = ((value - value.floor()) * 255.0).round() as usize;
+  let [right, down_left, down, sum] = OSTROMOUKHOV_COEFFICIENTS[level.min(255 - level)];
+  let sum = f32::from(sum);
+  [
+    tap(1, 0, f32::from(right) / sum),
+    tap(-1, 1, f32::from(down_left) / sum),
+    tap(0, 1, f32::from(down) / sum),
+  ]
+}
+
+/// Converts a plane with error diffusion.
+///
+/// [`DitherType::ErrorDiffusion`] is Floyd-Steinberg with a left-to-right
+/// scan, [`DitherType::ErrorDiffusionFmtc`] the same kernel with a serpentine
+/// scan. [`DitherType::Ostromoukhov`] is also serpentine; the other kernels
+/// scan left to right. Error spread outside of the plane is dropped.
+///
+/// # Errors
+///
+/// Returns [`VapoursError::UnsupportedDitherError`] if `dither_type` is not an
+/// error diffusion type, and [`VapoursError::UnsupportedFormatError`] if `src`
+/// and `dst` have different dimensions.
+pub fn error_diffusion<S, D>(
+  src: &PlaneView<'_, S>,
+  dst: &mut PlaneViewMut<'_, D>,
+  conversion: &DepthConversion,
+  dither_type: DitherType,
+) -> Result<(), VapoursError>
+where
+  S: Pixel,
+  D: Pixel,
+{
+  let Some(kernel) = Kernel::from_dither_type(dither_type) else {
+    return Err(VapoursError::UnsupportedDitherError(
+      dither_type,
+      NATIVE_BACKEND.to_string(),
+    ));
+  };
+  check_plane_dimensions(src, dst)?;
+
+  // Errors for the current line and the two below it, padded by two pixels on
+  // each side so that taps never need bounds checks.
+  let width = usize::try_from(src.width).unwrap_or_default();
+  let mut errors = [
+    vec![0.0f32; width + 4],
+    vec![0.0f32; width + 4],
+    vec![0.0f32; width + 4],
+  ];
+
+  for (y, (src_row, dst_row)) in zip_rows(src, dst).enumerate() {
+    let reverse = kernel.is_serpentine() && y % 2 == 1;
+    for i in 0..width {
+      let x = if reverse { width - 1 - i } else { i };
+      let value = conversion.scale(src_row[x].to_f32());
+      let wanted = value + errors[0][x + 2];
+      let out = conversion.quantize(wanted, 0.5);
+      dst_row[x] = D::from_f32(out);
+
+      let error = wanted - out;
+      let mut diffuse = |tap: Tap, weight: f32| {
+        let dx = if reverse { -tap.dx } else { tap.dx };
+        let column = (x + 2).wrapping_add_signed(dx);
+        errors[tap.dy][column] += error * weight;
+      };
+      match &kernel {
+        Kernel::Fixed { taps, divisor, .. } => {
+          for &tap in *taps {
+            diffuse(tap, tap.weight / divisor);
+          }
+        }
+        Kernel::Ostromoukhov => {
+          for tap in ostromoukhov_taps(value) {
+            diffuse(tap, tap.weight);
+          }
+        }
+      }
+    }
+
+    errors.rotate_left(1);
+    errors[2].fill(0.0);
+  }
+
+  Ok(())
+}
+
+/// Converts every plane of `src` to the format of `dst`, e.g. `YUV420P16` to
+/// `YUV420P10` or `RGBS` to `RGB24`, without relying on fmtconv or zimg.
+///
+/// Both frames must have the same dimensions, color family and subsampling.
+/// Ranges default to full for RGB and limited for everything else, chroma
+/// planes of YUV frames are scaled around their neutral value and
+/// [`DitherType::Auto`] is [resolved](DitherType::resolve) as in
+/// [`VapoursCore::depth`](crate::utils::VapoursCore::depth). The plane index
+/// given to [`dither_plane`] replaces [`DitherOptions::plane`].
+///
+/// # Errors
+///
+/// Returns [`VapoursError::UnsupportedFormatError`] if the frames are not
+/// compatible or if no pixel type matches one of their formats.
+pub fn convert_frame(
+  src: &VideoFrame,
+  dst: &mut VideoFrame,
+  dither_type: DitherType,
+  range_in: Option<ColorRange>,
+  range_out: Option<ColorRange>,
+  options: &DitherOptions,
+) -> Result<(), VapoursError> {
+  let conversion = FrameConversion::for_frames(
+    src.video_format().clone(),
+    dst.video_format().clone(),
+    (src.frame_width(0), src.frame_height(0)),
+    (dst.frame_width(0), dst.frame_height(0)),
+    dither_type,
+    range_in,
+    range_out,
+  )?;
+  dispatch(
+    &conversion.format_in,
+    ConvertFrom {
+      src,
+      dst,
+      conversion: &conversion,
+      options,
+    },
+  )?
+}
+
+/// The per-plane parameters of [`convert_frame`].
+struct FrameConversion {
+  format_in: VideoFormat,
+  format_out: VideoFormat,
+  dither_type: DitherType,
+  range_in: ColorRange,
+  range_out: ColorRange,
+}
+
+impl FrameConversion {
+  fn new(
+    format_in: VideoFormat,
+    format_out: VideoFormat,
+    dither_type: DitherType,
+    range_in: Option<ColorRange>,
+    range_out: Option<ColorRange>,
+  ) -> Self {
+    let default_range = if format_in.color_family == ColorFamily::RGB {
+      ColorRange::Full
+    } else {
+      ColorRange::Limited
+    };
+    let range_in = range_in.unwrap_or(default_range);
+    let range_out = range_out.unwrap_or(default_range);
+    let dither_type = dither_type.resolve(&format_in, &format_out, range_in, range_out);
+
+    Self {
+      format_in,
+      format_out,
+      dither_type,
+      range_in,
+      range_out,
+    }
+  }
+
+  /// Checks that frames of the given formats and dimensions can be converted
+  /// into one another, then resolves the defaults.
+  fn for_frames(
+    format_in: VideoFormat,
+    format_out: VideoFormat,
+    size_in: (i32, i32),
+    size_out: (i32, i32),
+    dither_type: DitherType,
+    range_in: Option<ColorRange>,
+    range_out: Option<ColorRange>,
+  ) -> Result<Self, VapoursError> {
+    if format_in.color_family != format_out.color_family
+      || format_in.sub_sampling_w != format_out.sub_sampling_w
+      || format_in.sub_sampling_h != format_out.sub_sampling_h
+    {
+      return Err(VapoursError::UnsupportedFormatError(
+        "frames differ in color family or subsampling".to_string(),
+      ));
+    }
+    if size_in != size_out {
+      return Err(VapoursError::UnsupportedFormatError(
+        "frames differ in dimensions".to_string(),
+      ));
+    }
+
+    Ok(Self::new(
+      format_in,
+      format_out,
+      dither_type,
+      range_in,
+      range_out,
+    ))
+  }
+
+  /// Converts the planes of a frame, in order.
+  fn convert_planes<'a, 'b, S, D>(
+    &self,
+    src_planes: impl Iterator<Item = PlaneView<'a, S>>,
+    dst_planes: impl Iterator<Item = PlaneViewMut<'b, D>>,
+    options: &DitherOptions,
+  ) -> Result<(), VapoursError>
+  where
+    S: Pixel,
+    D: Pixel,
+  {
+    let mut options = options.clone();
+    for (plane, (src, mut dst)) in zip(src_planes, dst_planes).enumerate() {
+      let chroma = self.format_in.color_family == ColorFamily::YUV && plane > 0;
+      let conversion = DepthConversion::new(
+        &self.format_in,
+        &self.format_out,
+        Some(self.range_in),
+        Some(self.range_out),
+        Some(chroma),
+      );
+      options.plane = plane as i32;
+      dither_plane(&src, &mut dst, &conversion, self.dither_type, &options)?;
+    }
+    Ok(())
+  }
+}
+
+/// Dispatches [`convert_frame`] on the source pixel type.
+struct ConvertFrom<'a> {
+  src: &'a VideoFrame,
+  dst: &'a mut VideoFrame,
+  conversion: &'a FrameConversion,
+  options: &'a DitherOptions,
+}
+
+impl PixelVisitor for ConvertFrom<'_> {
+  type Output = Result<(), VapoursError>;
+
+  fn visit<S: Pixel>(self) -> Self::Output {
+    let format_out = self.dst.video_format().clone();
+    dispatch(
+      &format_out,
+      ConvertTo::<S> {
+        src: self.src,
+        dst: self.dst,
+        conversion: self.conversion,
+        options: self.options,
+        _marker: PhantomData,
+      },
+    )?
+  }
+}
+
+/// Dispatches [`convert_frame`] on the destination pixel type.
+struct ConvertTo<'a, S> {
+  src: &'a VideoFrame,
+  dst: &'a mut VideoFrame,
+  conversion: &'a FrameConversion,
+  options: &'a DitherOptions,
+  _marker: PhantomData<S>,
+}
+
+impl<S: Pixel> PixelVisitor for ConvertTo<'_, S> {
+  type Output = Result<(), VapoursError>;
+
+  fn visit<D: Pixel>(self) -> Self::Output {
+    self.conversion.convert_planes(
+      self.src.planes_iter::<S>(),
+      self.dst.planes_iter_mut::<D>(),
+      self.options,
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use approx::assert_relative_eq;
+  use rstest::rstest;
+
+  use crate::vs_enums::{
+    GRAY16, GRAY8, GRAYS, RGB24, RGBS, YUV420P10, YUV420P16, YUV420P8, YUV444P8,
+  };
+
+  use super::*;
+
+  #[test]
+  fn test_bayer_matrix() {
+    assert_eq!(BayerMatrix::new(0).size(), 1);
+
+    let matrix = BayerMatrix::new(1);
+    assert_eq!(matrix.values, [0, 2, 3, 1]);
+    assert_eq!(matrix.value(2, 3), 3);
+
+    let matrix = BayerMatrix::new(2);
+    #[rustfmt::skip]
+    assert_eq!(
+      matrix.values,
+      [
+        0, 8, 2, 10,
+        12, 4, 14, 6,
+        3, 11, 1, 9,
+        15, 7, 13, 5,
+      ]
+    );
+
+    let mut values = BayerMatrix::default().values;
+    values.sort_unstable();
+    assert_eq!(values, (0..64).collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn test_void_cluster_matrix() {
+    let matrix = VoidClusterMatrix::new(4);
+    assert_eq!(matrix.size(), 16);
+    assert_eq!(matrix, VoidClusterMatrix::new(4));
+
+    let mut values = matrix.values.clone();
+    values.sort_unstable();
+    assert_eq!(values, (0..256).collect::<Vec<_>>());
+
+    // The lowest eighth of the ranks is spread out: no two of them are
+    // closer than 2 pixels.
+    let points: Vec<_> = (0..16)
+      .flat_map(|y| (0..16).map(move |x| (x, y)))
+      .filter(|&(x, y)| matrix.value(x, y) < 32)
+      .collect();
+    for (i, &(x1, y1)) in points.iter().enumerate() {
+      for &(x2, y2) in &points[i + 1..] {
+        let dx = x1.abs_diff(x2).min(16 - x1.abs_diff(x2));
+        let dy = y1.abs_diff(y2).min(16 - y1.abs_diff(y2));
+        assert!(dx * dx + dy * dy >= 4);
+      }
+    }
+  }
+
+  #[test]
+  fn test_void_cluster_matrix_cached() {
+    let matrix = VoidClusterMatrix::cached(3);
+    assert!(Arc::ptr_eq(&matrix, &VoidClusterMatrix::cached(3)));
+    assert_eq!(*matrix, VoidClusterMatrix::new(3));
+
+    let matrices: Vec<_> = std::thread::scope(|scope| {
+      let handles: Vec<_> = [2, 2, 5, 2]
+        .map(|size_log2| scope.spawn(move || VoidClusterMatrix::cached(size_log2)))
+        .into_iter()
+        .collect();
+      handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect()
+    });
+    assert!(Arc::ptr_eq(&matrices[0], &matrices[1]));
+    assert!(Arc::ptr_eq(&matrices[0], &matrices[3]));
+    assert_eq!(matrices[2].size(), 32);
+  }
+
+  #[test]
+  fn test_r2_sequence() {
+    assert_relative_eq!(R2Sequence.threshold(0, 0), 0.0);
+    assert_relative_eq!(R2Sequence.threshold(1, 0), 0.754_877_7);
+    assert_relative_eq!(R2Sequence.threshold(0, 1), 0.569_840_3);
+    assert_relative_eq!(R2Sequence.threshold(1, 1), 0.324_718);
+  }
+
+  #[test]
+  fn test_random_noise() {
+    let noise = RandomNoise::new(42, 7, 1);
+    assert_eq!(noise, RandomNoise::new(42, 7, 1));
+    assert_ne!(noise, RandomNoise::new(43, 7, 1));
+    assert_ne!(noise, RandomNoise::new(42, 8, 1));
+    assert_ne!(noise, RandomNoise::new(42, 7, 2));
+
+    let thresholds: Vec<f32> = (0..64)
+      .flat_map(|y| (0..64).map(move |x| noise.threshold(x, y)))
+      .collect();
+    assert!(thresholds.iter().all(|t| (0.0..1.0).contains(t)));
+    let mean = thresholds.iter().sum::<f32>() / 4096.0;
+    assert_relative_eq!(mean, 0.5, epsilon = 0.02);
+  }
+
+  #[test]
+  fn test_convert_planes_yuv() {
+    let luma = [25728u16; 4];
+    let (u, v) = ([32768u16], [36864u16]);
+    let src_planes = [&luma[..], &u, &v].map(|data| PlaneView {
+      data,
+      height: if data.len() == 4 { 2 } else { 1 },
+      width: if data.len() == 4 { 2 } else { 1 },
+      stride: if data.len() == 4 { 4 } else { 2 },
+    });
+    let (mut dst_luma, mut dst_u, mut dst_v) = ([0u8; 4], [0u8], [0u8]);
+    let dst_planes = [
+      PlaneViewMut {
+        data: &mut dst_luma[..],
+        height: 2,
+        width: 2,
+        stride: 2,
+      },
+      PlaneViewMut {
+        data: &mut dst_u[..],
+        height: 1,
+        width: 1,
+        stride: 1,
+      },
+      PlaneViewMut {
+        data: &mut dst_v[..],
+        height: 1,
+        width: 1,
+        stride: 1,
+      },
+    ];
+
+    let conversion = FrameConversion::new(YUV420P16, YUV420P8, DitherType::None, None, None);
+    conversion
+      .convert_planes(
+        src_planes.into_iter(),
+        dst_planes.into_iter(),
+        &DitherOptions::default(),
+      )
+      .unwrap();
+    assert_eq!(dst_luma, [101; 4]);
+    assert_eq!(dst_u, [128]);
+    assert_eq!(dst_v, [144]);
+  }
+
+  #[test]
+  fn test_convert_planes_rgb_from_float() {
+    let src_data = [[0.5f32, 1.0], [0.2, -0.1], [0.0, 2.0]];
+    let src_planes = src_data.iter().map(|data| PlaneView {
+      data,
+      height: 1,
+      width: 2,
+      stride: 8,
+    });
+    let mut dst_data = [[0u8; 2]; 3];
+    let dst_planes = dst_data.iter_mut().map(|data| PlaneViewMut {
+      data,
+      height: 1,
+      width: 2,
+      stride: 2,
+    });
+
+    let conversion = FrameConversion::new(RGBS, RGB24, DitherType::Auto, None, None);
+    assert_eq!(conversion.dither_type, DitherType::ErrorDiffusion);
+    conversion
+      .convert_planes(src_planes, dst_planes, &DitherOptions::default())
+      .unwrap();
+    assert_eq!(dst_data, [[128, 255], [51, 0], [0, 255]]);
+  }
+
+  /// Converts the planes of a frame of `width` by `height` pixels as
+  /// [`convert_frame`] does, with planes sized from the formats.
+  #[allow(clippy::too_many_arguments)]
+  fn convert_test_frame<S: Pixel, D: Pixel>(
+    format_in: VideoFormat,
+    format_out: VideoFormat,
+    width: i32,
+    height: i32,
+    src_data: &[Vec<S>],
+    dither_type: DitherType,
+    range_in: Option<ColorRange>,
+    range_out: Option<ColorRange>,
+  ) -> Vec<Vec<D>> {
+    let conversion = FrameConversion::for_frames(
+      format_in,
+      format_out,
+      (width, height),
+      (width, height),
+      dither_type,
+      range_in,
+      range_out,
+    )
+    .unwrap();
+
+    let format_in = &conversion.format_in;
+    let size = |plane: usize| {
+      (
+        format_in.plane_width(plane as i32, width),
+        format_in.plane_height(plane as i32, height),
+      )
+    };
+    let src_planes = src_data.iter().enumerate().map(|(plane, data)| {
+      let (width, height) = size(plane);
+      assert_eq!(data.len(), (width * height) as usize);
+      PlaneView {
+        data,
+        height,
+        width,
+        stride: (width as usize * size_of::<S>()) as isize,
+      }
+    });
+    let mut dst_data: Vec<Vec<D>> = src_data
+      .iter()
+      .map(|data| vec![D::default(); data.len()])
+      .collect();
+    let dst_planes = dst_data.iter_mut().enumerate().map(|(plane, data)| {
+      let (width, height) = size(plane);
+      PlaneViewMut {
+        data,
+        height,
+        width,
+        stride: (width as usize * size_of::<D>()) as isize,
+      }
+    });
+
+    conversion
+      .convert_planes(src_planes, dst_planes, &DitherOptions::default())
+      .unwrap();
+    dst_data
+  }
+
+  #[test]
+  fn test_convert_frame_yuv420p16_to_yuv420p10() {
+    let src_data = vec![
+      vec![4096u16, 60160, 25728, 32768, 4096, 60160, 25728, 32768],
+      vec![32768, 61440],
+      vec![4096, 32768],
+    ];
+
+    // Limited to limited: luma and chroma are both divided by 64, chroma
+    // around its neutral value.
+    let dst_data = convert_test_frame::<_, u16>(
+      YUV420P16,
+      YUV420P10,
+      4,
+      2,
+      &src_data,
+      DitherType::Auto,
+      None,
+      None,
+    );
+    assert_eq!(
+      dst_data,
+      [
+        vec![64, 940, 402, 512, 64, 940, 402, 512],
+        vec![512, 960],
+        vec![64, 512],
+      ]
+    );
+
+    // Limited to full: luma is stretched from [64, 940] to [0, 1023] and
+    // chroma from [64, 960] to [0, 1023] around 512.
+    let dst_data = convert_test_frame::<_, u16>(
+      YUV420P16,
+      YUV420P10,
+      4,
+      2,
+      &src_data,
+      DitherType::None,
+      None,
+      Some(ColorRange::Full),
+    );
+    assert_eq!(
+      dst_data,
+      [
+        vec![0, 1023, 395, 523, 0, 1023, 395, 523],
+        vec![512, 1023],
+        vec![1, 512],
+      ]
+    );
+  }
+
+  #[test]
+  fn test_convert_frame_rgbs_to_rgb24() {
+    let src_data = vec![
+      vec![0.0f32, 0.5, 1.0, 1.2],
+      vec![-0.1, 0.2, 0.75, 0.25],
+      vec![1.0, 0.0, 0.5, 0.1],
+    ];
+
+    let dst_data =
+      convert_test_frame::<_, u8>(RGBS, RGB24, 2, 2, &src_data, DitherType::None, None, None);
+    assert_eq!(
+      dst_data,
+      [
+        vec![0, 128, 255, 255],
+        vec![0, 51, 191, 64],
+        vec![255, 0, 128, 26],
+      ]
+    );
+
+    // Full to limited: [0, 1] maps to [16, 235], with no chroma offset for
+    // RGB. As in `scale_value`, values outside of [0, 1] are only clamped to
+    // the output bit depth.
+    let dst_data = convert_test_frame::<_, u8>(
+      RGBS,
+      RGB24,
+      2,
+      2,
+      &src_data,
+      DitherType::None,
+      None,
+      Some(ColorRange::Limited),
+    );
+    assert_eq!(
+      dst_data,
+      [
+        vec![16, 126, 235, 255],
+        vec![0, 60, 180, 71],
+        vec![235, 16, 126, 38],
+      ]
+    );
+  }
+
+  #[test]
+  fn test_convert_frame_mismatch() {
+    let convert = |format_out, size_out| {
+      FrameConversion::for_frames(
+        YUV420P16,
+        format_out,
+        (4, 2),
+        size_out,
+        DitherType::Auto,
+        None,
+        None,
+      )
+    };
+    assert!(convert(YUV420P10, (4, 2)).is_ok());
+    assert!(matches!(
+      convert(YUV444P8, (4, 2)),
+      Err(VapoursError::UnsupportedFormatError(_))
+    ));
+    assert!(matches!(
+      convert(RGB24, (4, 2)),
+      Err(VapoursError::UnsupportedFormatError(_))
+    ));
+    assert!(matches!(
+      convert(YUV420P10, (4, 4)),
+      Err(VapoursError::UnsupportedFormatError(_))
+    ));
+  }
+
+  #[test]
+  fn test_depth_conversion() {
+    let conversion = DepthConversion::new(&GRAY16, &GRAY8, None, None, None);
+    assert_relative_eq!(conversion.scale(25728.0), 100.5);
+    assert_relative_eq!(conversion.quantize(100.5, 0.5), 101.0);
+    assert_relative_eq!(conversion.quantize(100.5, 0.25), 100.0);
+    assert_relative_eq!(conversion.quantize(300.0, 0.5), 255.0);
+
+    assert_eq!(
+      DepthConversion::from(ScaleParams::builder(&GRAY16, &GRAY8).build()),
+      conversion
+    );
+
+    let conversion = DepthConversion::new(&RGB24, &RGBS, None, None, None);
+    assert!(!conversion.is_integer_out());
+    assert_relative_eq!(conversion.quantize(conversion.scale(51.0), 0.0), 0.2);
+  }
+
+  #[test]
+  fn test_ordered_dither() {
+    let src_data = [25728u16; 8];
+    let src = PlaneView {
+      data: &src_data,
+      height: 2,
+      width: 3,
+      stride: 8,
+    };
+    let mut dst_data = [0u8; 6];
+    let mut dst = PlaneViewMut {
+      data: &mut dst_data,
+      height: 2,
+      width: 3,
+      stride: 3,
+    };
+
+    let conversion = DepthConversion::new(&GRAY16, &GRAY8, None, None, None);
+    ordered_dither(&src, &mut dst, &conversion, &BayerMatrix::new(1)).unwrap();
+    assert_eq!(dst_data, [100, 101, 100, 101, 100, 101]);
+  }
+
+  #[test]
+  fn test_ordered_dither_from_float() {
+    let src_data = [0.0f32, 0.5, 1.0, 1.5];
+    let src = PlaneView {
+      data: &src_data,
+      height: 1,
+      width: 4,
+      stride: 16,
+    };
+    let mut dst_data = [0u8; 4];
+    let mut dst = PlaneViewMut {
+      data: &mut dst_data,
+      height: 1,
+      width: 4,
+      stride: 4,
+    };
+
+    let conversion = DepthConversion::new(&GRAYS, &GRAY8, None, None, None);
+    ordered_dither(&src, &mut dst, &conversion, &BayerMatrix::default()).unwrap();
+    assert_eq!(dst_data, [16, 126, 235, 255]);
+  }
+
+  #[test]
+  fn test_ostromoukhov_coefficients() {
+    for [right, down_left, down, sum] in OSTROMOUKHOV_COEFFICIENTS {
+      assert_eq!(right + down_left + down, sum);
+    }
+  }
+
+  #[test]
+  fn test_error_diffusion_flat() {
+    // 100.375 after scaling.
+    let src_data = [25696u16; 4];
+    let src = PlaneView {
+      data: &src_data,
+      height: 1,
+      width: 4,
+      stride: 8,
+    };
+    let mut dst_data = [0u8; 4];
+    let mut dst = PlaneViewMut {
+      data: &mut dst_data,
+      height: 1,
+      width: 4,
+      stride: 4,
+    };
+
+    let conversion = DepthConversion::new(&GRAY16, &GRAY8, None, None, None);
+    error_diffusion(&src, &mut dst, &conversion, DitherType::ErrorDiffusion).unwrap();
+    assert_eq!(dst_data, [100, 101, 100, 100]);
+  }
+
+  #[test]
+  fn test_error_diffusion_checkerboard() {
+    // Floyd-Steinberg turns a level halfway between two output values into a
+    // checkerboard. By hand, with a threshold of 0.5 on 100.5 (25728):
+    // - row 0: 101 (-0.5), 100.281 (+0.281), 100.623 (-0.377), 100.335;
+    // - row 1: 100.396 (+0.396), 100.659 (-0.341), 100.314 (+0.314),
+    //   100.718.
+    let src_data = [25728u16; 8];
+    let src = PlaneView {
+      data: &src_data,
+      height: 2,
+      width: 4,
+      stride: 8,
+    };
+    let mut dst_data = [0u8; 8];
+    let mut dst = PlaneViewMut {
+      data: &mut dst_data,
+      height: 2,
+      width: 4,
+      stride: 4,
+    };
+
+    let conversion = DepthConversion::new(&GRAY16, &GRAY8, None, None, None);
+    error_diffusion(&src, &mut dst, &conversion, DitherType::ErrorDiffusion).unwrap();
+    assert_eq!(dst_data, [101, 100, 101, 100, 100, 101, 100, 101]);
+  }
+
+  // Regression outputs of every kernel on a gradient. The flat and
+  // checkerboard cases above are checked by hand.
+  #[rustfmt::skip]
+  #[rstest]
+  #[case(DitherType::ErrorDiffusion, [
+    78, 82, 86, 90, 94, 97,
+    84, 88, 92, 96, 99, 104,
+    90, 94, 98, 102, 106, 109,
+    96, 100, 104, 107, 112, 116,
+  ])]
+  #[case(DitherType::ErrorDiffusionFmtc, [
+    78, 82, 86, 90, 94, 97,
+    84, 88, 92, 96, 99, 104,
+    90, 94, 98, 102, 106, 109,
+    96, 100, 104, 108, 111, 116,
+  ])]
+  #[case(DitherType::Sierra24a, [
+    78, 82, 86, 90, 94, 97,
+    84, 88, 92, 96, 99, 104,
+    90, 94, 98, 102, 106, 109,
+    96, 100, 104, 107, 112, 116,
+  ])]
+  #[case(DitherType::Stucki, [
+    78, 82, 86, 90, 94, 98,
+    84, 88, 92, 96, 99, 104,
+    90, 94, 98, 102, 106, 109,
+    96, 100, 104, 108, 111, 116,
+  ])]
+  #[case(DitherType::Atkinson, [
+    78, 82, 86, 90, 94, 98,
+    84, 88, 92, 96, 100, 103,
+    90, 94, 98, 102, 106, 109,
+    96, 100, 104, 108, 111, 116,
+  ])]
+  #[case(DitherType::Ostromoukhov, [
+    78, 82, 86, 90, 94, 97,
+    84, 88, 92, 95, 100, 104,
+    90, 94, 98, 102, 106, 109,
+    96, 100, 104, 108, 111, 116,
+  ])]
+  fn test_error_diffusion_gradient(#[case] dither_type: DitherType, #[case] expected: [u8; 24]) {
+    let src_data: Vec<u16> = (0..4)
+      .flat_map(|y| (0..6).map(move |x| 20000 + 997 * x + 1531 * y))
+      .collect();
+    let src = PlaneView {
+      data: &src_data,
+      height: 4,
+      width: 6,
+      stride: 12,
+    };
+    let mut dst_data = [0u8; 24];
+    let mut dst = PlaneViewMut {
+      data: &mut dst_data,
+      height: 4,
+      width: 6,
+      stride: 6,
+    };
+
+    let conversion = DepthConversion::new(&GRAY16, &GRAY8, None, None, None);
+    error_diffusion(&src, &mut dst, &conversion, dither_type).unwrap();
+    assert_eq!(dst_data, expected);
+  }
+
+  #[rstest]
+  #[case(DitherType::None, [100, 100, 100, 100])]
+  #[case(DitherType::Auto, [100, 101, 100, 100])]
+  #[case(DitherType::Ordered, [100, 100, 100, 101])]
+  #[case(DitherType::Void, [100, 101, 101, 100])]
+  #[case(DitherType::Quasirandom, [100, 101, 100, 100])]
+  #[case(DitherType::Random, [101, 101, 100, 101])]
+  fn test_dither_plane(#[case] dither_type: DitherType, #[case] expected: [u8; 4]) {
+    let src_data = [25696u16; 4];
+    let src = PlaneView {
+      data: &src_data,
+      height: 1,
+      width: 4,
+      stride: 8,
+    };
+    let mut dst_data = [0u8; 4];
+    let mut dst = PlaneViewMut {
+      data: &mut dst_data,
+      height: 1,
+      width: 4,
+      stride: 4,
+    };
+
+    let conversion = DepthConversion::new(&GRAY16, &GRAY8, None, None, None);
+    dither_plane(
+      &src,
+      &mut dst,
+      &conversion,
+      dither_type,
+      &DitherOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(dst_data, expected);
+  }
+
+  #[test]
+  fn test_error_diffusion_unsupported() {
+    let src = PlaneView {
+      data: &[0u16],
+      height: 1,
+      width: 1,
+      stride: 2,
+    };
+    let mut dst_data = [0u8];
+    let mut dst = PlaneViewMut {
+      data: &mut dst_data,
+      height: 1,
+      width: 1,
+      stride: 1,
+    };
+
+    let conversion = DepthConversion::new(&GRAY16, &GRAY8, None, None, None);
+    let result = error_diffusion(&src, &mut dst, &conversion, DitherType::Random);
+    assert!(matches!(
+      result,
+      Err(VapoursError::UnsupportedDitherError(DitherType::Random, _))
+    ));
+  }
+
+  #[test]
+  fn test_dither_plane_void_too_large() {
+    let src = PlaneView {
+      data: &[0u16],
+      height: 1,
+      width: 1,
+      stride: 2,
+    };
+    let mut dst_data = [0u8];
+    let mut dst = PlaneViewMut {
+      data: &mut dst_data,
+      height: 1,
+      width: 1,
+      stride: 1,
+    };
+
+    let conversion = DepthConversion::new(&GRAY16, &GRAY8, None, None, None);
+    let options = DitherOptions {
+      void_size_log2: VoidClusterMatrix::MAX_SIZE_LOG2 + 1,
+      ..DitherOptions::default()
+    };
+    let result = dither_plane(&src, &mut dst, &conversion, DitherType::Void, &options);
+    assert!(matches!(
+      result,
+      Err(VapoursError::UnsupportedDitherError(DitherType::Void, _))
+    ));
+  }
+
+  #[test]
+  fn test_dither_mismatched_dimensions() {
+    let src_data = [0u16; 8];
+    let src = PlaneView {
+      data: &src_data,
+      height: 2,
+      width: 4,
+      stride: 8,
+    };
+    let mut dst_data = [0u8; 8];
+    let mut dst = PlaneViewMut {
+      data: &mut dst_data,
+      height: 2,
+      width: 3,
+      stride: 4,
+    };
+
+    let conversion = DepthConversion::new(&GRAY16, &GRAY8, None, None, None);
+    let is_mismatch = |result: Result<(), VapoursError>| matches!(result, Err(VapoursError::UnsupportedFormatError(message)) if message == "cannot convert a 4x2 plane into a 3x2 plane");
+    assert!(is_mismatch(error_diffusion(
+      &src,
+      &mut dst,
+      &conversion,
+      DitherType::ErrorDiffusion
+    )));
+    assert!(is_mismatch(ordered_dither(
+      &src,
+      &mut dst,
+      &conversion,
+      &BayerMatrix::default()
+    )));
+    for dither_type in [DitherType::None, DitherType::Void, DitherType::Stucki] {
+      assert!(is_mismatch(dither_plane(
+        &src,
+        &mut dst,
+        &conversion,
+        dither_type,
+        &DitherOptions::default()
+      )));
+    }
+    assert_eq!(dst_data, [0; 8]);
+  }
+}
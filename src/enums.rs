@@ -1,8 +1,18 @@
 //! Enums.
 
+use num_traits::FromPrimitive;
+use strum_macros::{AsRefStr, EnumString};
+use vapoursynth4_rs::{frame::VideoFrame, key, map::KeyStr, ColorFamily};
+
+use crate::{
+  errors::{PropertyErrorReason, VapoursError},
+  generic::HoldsVideoFormat,
+  props::VapoursFrameProps,
+};
+
 /// Pixel range ([ITU-T H.265](https://www.itu.int/rec/T-REC-H.265) Equations
 /// E-10 through E-20).
-#[derive(Clone, Copy, Debug, Eq, FromPrimitive, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, FromPrimitive, PartialEq, ToPrimitive)]
 pub enum ColorRange {
   /// Studio (TV) legal range, 16-235 in 8 bits. This is primarily used with
   /// YUV integer formats.
@@ -13,9 +23,533 @@ pub enum ColorRange {
   Full = 0,
 }
 
+impl ColorRange {
+  /// Reads the range from the `_ColorRange` property of a frame.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the property is missing or is not a valid range.
+  pub fn from_frame(frame: &VideoFrame) -> Result<Self, VapoursError> {
+    get_enum_prop(frame, key!(c"_ColorRange"))
+  }
+
+  /// Writes the range to the `_ColorRange` property of a frame.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the property could not be set.
+  pub fn set_on_frame(self, frame: &mut VideoFrame) -> Result<(), VapoursError> {
+    set_int_prop(frame, key!(c"_ColorRange"), self as i64)
+  }
+
+  /// Returns the value of this range as understood by zimg (`range` and
+  /// `range_in` arguments of the `resize` plugin), which is the inverse of the
+  /// `_ColorRange` frame property.
+  #[must_use]
+  pub const fn value_zimg(self) -> i64 {
+    match self {
+      Self::Limited => 0,
+      Self::Full => 1,
+    }
+  }
+}
+
+/// Matrix coefficients ([ITU-T H.273](https://www.itu.int/rec/T-REC-H.273)
+/// Table 4).
+///
+/// String parsing accepts both the names used by the `resize` plugin (e.g.
+/// `709`, `170m`) and their `bt`/`smpte` prefixed forms.
+#[derive(AsRefStr, Clone, Copy, Debug, EnumString, Eq, FromPrimitive, PartialEq, ToPrimitive)]
+#[strum(ascii_case_insensitive)]
+pub enum Matrix {
+  /// The identity matrix. Typically used for GBR (often referred to as RGB).
+  #[strum(to_string = "rgb", serialize = "gbr")]
+  Rgb = 0,
+
+  /// ITU-R BT.709-6.
+  #[strum(to_string = "709", serialize = "bt709")]
+  Bt709 = 1,
+
+  /// Image characteristics are unknown or are determined by the application.
+  #[strum(to_string = "unspec", serialize = "unspecified")]
+  Unspecified = 2,
+
+  /// United States Federal Communications Commission Title 47.
+  #[strum(to_string = "fcc")]
+  Fcc = 4,
+
+  /// ITU-R BT.470-6 System B, G and ITU-R BT.601-7 625.
+  #[strum(to_string = "470bg", serialize = "bt470bg")]
+  Bt470bg = 5,
+
+  /// SMPTE ST 170 and ITU-R BT.601-7 525. Functionally the same as
+  /// [`Matrix::Bt470bg`].
+  #[strum(to_string = "170m", serialize = "smpte170m")]
+  Smpte170m = 6,
+
+  /// SMPTE ST 240.
+  #[strum(to_string = "240m", serialize = "smpte240m")]
+  Smpte240m = 7,
+
+  /// `YCgCo`.
+  #[strum(to_string = "ycgco")]
+  YCgCo = 8,
+
+  /// ITU-R BT.2020-2 non-constant luminance.
+  #[strum(to_string = "2020ncl", serialize = "bt2020ncl")]
+  Bt2020ncl = 9,
+
+  /// ITU-R BT.2020-2 constant luminance.
+  #[strum(to_string = "2020cl", serialize = "bt2020cl")]
+  Bt2020cl = 10,
+
+  /// Chromaticity-derived non-constant luminance.
+  #[strum(to_string = "chromancl")]
+  ChromaNcl = 12,
+
+  /// Chromaticity-derived constant luminance.
+  #[strum(to_string = "chromacl")]
+  ChromaCl = 13,
+
+  /// ITU-R BT.2100-0 `ICtCp`.
+  #[strum(to_string = "ictcp")]
+  ICtCp = 14,
+}
+
+impl Matrix {
+  /// Reads the matrix from the `_Matrix` property of a frame.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the property is missing or is not a valid matrix.
+  pub fn from_frame(frame: &VideoFrame) -> Result<Self, VapoursError> {
+    get_enum_prop(frame, key!(c"_Matrix"))
+  }
+
+  /// Writes the matrix to the `_Matrix` property of a frame.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the property could not be set.
+  pub fn set_on_frame(self, frame: &mut VideoFrame) -> Result<(), VapoursError> {
+    set_int_prop(frame, key!(c"_Matrix"), self as i64)
+  }
+}
+
+/// Transfer characteristics ([ITU-T H.273](https://www.itu.int/rec/T-REC-H.273)
+/// Table 3).
+///
+/// String parsing accepts the names used by the `resize` plugin (e.g. `709`,
+/// `st2084`) as well as a few common aliases.
+#[derive(AsRefStr, Clone, Copy, Debug, EnumString, Eq, FromPrimitive, PartialEq, ToPrimitive)]
+#[strum(ascii_case_insensitive)]
+pub enum Transfer {
+  /// ITU-R BT.709-6.
+  #[strum(to_string = "709", serialize = "bt709")]
+  Bt709 = 1,
+
+  /// Image characteristics are unknown or are determined by the application.
+  #[strum(to_string = "unspec", serialize = "unspecified")]
+  Unspecified = 2,
+
+  /// ITU-R BT.470-6 System M.
+  #[strum(to_string = "470m", serialize = "bt470m")]
+  Bt470m = 4,
+
+  /// ITU-R BT.470-6 System B, G.
+  #[strum(to_string = "470bg", serialize = "bt470bg")]
+  Bt470bg = 5,
+
+  /// ITU-R BT.601-7. Functionally the same as [`Transfer::Bt709`].
+  #[strum(to_string = "601", serialize = "bt601")]
+  Bt601 = 6,
+
+  /// SMPTE ST 240.
+  #[strum(to_string = "240m", serialize = "smpte240m")]
+  St240m = 7,
+
+  /// Linear transfer characteristics.
+  #[strum(to_string = "linear")]
+  Linear = 8,
+
+  /// Logarithmic transfer characteristic (100:1 range).
+  #[strum(to_string = "log100")]
+  Log100 = 9,
+
+  /// Logarithmic transfer characteristic (100 * sqrt(10):1 range).
+  #[strum(to_string = "log316")]
+  Log316 = 10,
+
+  /// IEC 61966-2-4 (xvYCC).
+  #[strum(to_string = "xvycc", serialize = "iec61966_2_4")]
+  Iec61966_2_4 = 11,
+
+  /// IEC 61966-2-1 (sRGB).
+  #[strum(to_string = "srgb", serialize = "iec61966_2_1")]
+  Iec61966_2_1 = 13,
+
+  /// ITU-R BT.2020-2 for 10-bit systems. Functionally the same as
+  /// [`Transfer::Bt709`].
+  #[strum(to_string = "2020_10", serialize = "bt2020_10")]
+  Bt2020_10 = 14,
+
+  /// ITU-R BT.2020-2 for 12-bit systems. Functionally the same as
+  /// [`Transfer::Bt709`].
+  #[strum(to_string = "2020_12", serialize = "bt2020_12")]
+  Bt2020_12 = 15,
+
+  /// SMPTE ST 2084 (PQ).
+  #[strum(to_string = "st2084", serialize = "pq")]
+  St2084 = 16,
+
+  /// ARIB STD-B67 (HLG).
+  #[strum(to_string = "std-b67", serialize = "hlg")]
+  AribB67 = 18,
+}
+
+impl Transfer {
+  /// Reads the transfer characteristics from the `_Transfer` property of a
+  /// frame.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the property is missing or is not a valid transfer.
+  pub fn from_frame(frame: &VideoFrame) -> Result<Self, VapoursError> {
+    get_enum_prop(frame, key!(c"_Transfer"))
+  }
+
+  /// Writes the transfer characteristics to the `_Transfer` property of a
+  /// frame.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the property could not be set.
+  pub fn set_on_frame(self, frame: &mut VideoFrame) -> Result<(), VapoursError> {
+    set_int_prop(frame, key!(c"_Transfer"), self as i64)
+  }
+
+  /// Guesses the transfer characteristics from the resolution of a clip.
+  ///
+  /// RGB is assumed to be sRGB. Otherwise SD resolutions are assumed to be
+  /// BT.601, HD resolutions BT.709, and anything larger BT.2020, for 12-bit
+  /// systems if the clip has more than 10 bits.
+  #[must_use]
+  pub fn from_res<T: HoldsVideoFormat>(clip: &T, width: i32, height: i32) -> Self {
+    if clip.color_family() == ColorFamily::RGB {
+      return Self::Iec61966_2_1;
+    }
+
+    match Resolution::new(width, height) {
+      Resolution::Sd => Self::Bt601,
+      Resolution::Hd => Self::Bt709,
+      Resolution::Uhd if clip.depth() > 10 => Self::Bt2020_12,
+      Resolution::Uhd => Self::Bt2020_10,
+    }
+  }
+
+  /// Gets the transfer characteristics of a clip, reading them from the
+  /// `_Transfer` property of `frame` if given. Falls back to
+  /// [`Transfer::from_res`] if there is no frame or the property is missing
+  /// or [`Transfer::Unspecified`].
+  #[must_use]
+  pub fn from_video<T: HoldsVideoFormat>(
+    clip: &T,
+    width: i32,
+    height: i32,
+    frame: Option<&VideoFrame>,
+  ) -> Self {
+    match frame.map(Self::from_frame) {
+      Some(Ok(transfer)) if transfer != Self::Unspecified => transfer,
+      _ => Self::from_res(clip, width, height),
+    }
+  }
+}
+
+/// Color primaries ([ITU-T H.273](https://www.itu.int/rec/T-REC-H.273)
+/// Table 2).
+///
+/// String parsing accepts the names used by the `resize` plugin (e.g. `709`,
+/// `dci-p3`) as well as a few common aliases.
+#[derive(AsRefStr, Clone, Copy, Debug, EnumString, Eq, FromPrimitive, PartialEq, ToPrimitive)]
+#[strum(ascii_case_insensitive)]
+pub enum Primaries {
+  /// ITU-R BT.709-6.
+  #[strum(to_string = "709", serialize = "bt709")]
+  Bt709 = 1,
+
+  /// Image characteristics are unknown or are determined by the application.
+  #[strum(to_string = "unspec", serialize = "unspecified")]
+  Unspecified = 2,
+
+  /// ITU-R BT.470-6 System M.
+  #[strum(to_string = "470m", serialize = "bt470m")]
+  Bt470m = 4,
+
+  /// ITU-R BT.470-6 System B, G and ITU-R BT.601-7 625.
+  #[strum(to_string = "470bg", serialize = "bt470bg")]
+  Bt470bg = 5,
+
+  /// SMPTE ST 170 and ITU-R BT.601-7 525.
+  #[strum(to_string = "170m", serialize = "smpte170m")]
+  Smpte170m = 6,
+
+  /// SMPTE ST 240. Functionally the same as [`Primaries::Smpte170m`].
+  #[strum(to_string = "240m", serialize = "smpte240m")]
+  Smpte240m = 7,
+
+  /// Generic film (color filters using Illuminant C).
+  #[strum(to_string = "film")]
+  Film = 8,
+
+  /// ITU-R BT.2020-2.
+  #[strum(to_string = "2020", serialize = "bt2020")]
+  Bt2020 = 9,
+
+  /// SMPTE ST 428-1 (CIE 1931 XYZ).
+  #[strum(to_string = "xyz", serialize = "st428")]
+  St428 = 10,
+
+  /// SMPTE RP 431-2 (DCI-P3).
+  #[strum(to_string = "dci-p3", serialize = "st431_2")]
+  St431_2 = 11,
+
+  /// SMPTE EG 432-1 (Display P3).
+  #[strum(to_string = "display-p3", serialize = "st432_1")]
+  St432_1 = 12,
+
+  /// EBU Tech. 3213-E.
+  #[strum(to_string = "jedec-p22", serialize = "ebu3213e")]
+  Ebu3213e = 22,
+}
+
+impl Primaries {
+  /// Reads the color primaries from the `_Primaries` property of a frame.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the property is missing or is not valid primaries.
+  pub fn from_frame(frame: &VideoFrame) -> Result<Self, VapoursError> {
+    get_enum_prop(frame, key!(c"_Primaries"))
+  }
+
+  /// Writes the color primaries to the `_Primaries` property of a frame.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the property could not be set.
+  pub fn set_on_frame(self, frame: &mut VideoFrame) -> Result<(), VapoursError> {
+    set_int_prop(frame, key!(c"_Primaries"), self as i64)
+  }
+
+  /// Guesses the color primaries from the resolution of a clip.
+  ///
+  /// RGB is assumed to be BT.709. Otherwise SD resolutions are assumed to be
+  /// BT.470BG if 576 pixels high and SMPTE 170M if not, HD resolutions BT.709,
+  /// and anything larger BT.2020.
+  #[must_use]
+  pub fn from_res<T: HoldsVideoFormat>(clip: &T, width: i32, height: i32) -> Self {
+    if clip.color_family() == ColorFamily::RGB {
+      return Self::Bt709;
+    }
+
+    match Resolution::new(width, height) {
+      Resolution::Sd if height == 576 => Self::Bt470bg,
+      Resolution::Sd => Self::Smpte170m,
+      Resolution::Hd => Self::Bt709,
+      Resolution::Uhd => Self::Bt2020,
+    }
+  }
+
+  /// Gets the color primaries of a clip, reading them from the `_Primaries`
+  /// property of `frame` if given. Falls back to [`Primaries::from_res`] if
+  /// there is no frame or the property is missing or
+  /// [`Primaries::Unspecified`].
+  #[must_use]
+  pub fn from_video<T: HoldsVideoFormat>(
+    clip: &T,
+    width: i32,
+    height: i32,
+    frame: Option<&VideoFrame>,
+  ) -> Self {
+    match frame.map(Self::from_frame) {
+      Some(Ok(primaries)) if primaries != Self::Unspecified => primaries,
+      _ => Self::from_res(clip, width, height),
+    }
+  }
+}
+
+/// Chroma sample location ([ITU-T H.273](https://www.itu.int/rec/T-REC-H.273)
+/// Figure 2).
+#[derive(AsRefStr, Clone, Copy, Debug, EnumString, Eq, FromPrimitive, PartialEq, ToPrimitive)]
+#[strum(ascii_case_insensitive, serialize_all = "snake_case")]
+pub enum ChromaLocation {
+  /// Horizontally co-sited with the left luma sample, vertically centered.
+  Left = 0,
+
+  /// Centered between the luma samples.
+  Center = 1,
+
+  /// Co-sited with the top left luma sample.
+  TopLeft = 2,
+
+  /// Horizontally centered, vertically co-sited with the top luma sample.
+  Top = 3,
+
+  /// Horizontally co-sited with the left luma sample, vertically co-sited
+  /// with the bottom luma sample.
+  BottomLeft = 4,
+
+  /// Horizontally centered, vertically co-sited with the bottom luma sample.
+  Bottom = 5,
+}
+
+impl ChromaLocation {
+  /// Reads the chroma location from the `_ChromaLocation` property of a frame.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the property is missing or is not a valid chroma
+  /// location.
+  pub fn from_frame(frame: &VideoFrame) -> Result<Self, VapoursError> {
+    get_enum_prop(frame, key!(c"_ChromaLocation"))
+  }
+
+  /// Writes the chroma location to the `_ChromaLocation` property of a frame.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the property could not be set.
+  pub fn set_on_frame(self, frame: &mut VideoFrame) -> Result<(), VapoursError> {
+    set_int_prop(frame, key!(c"_ChromaLocation"), self as i64)
+  }
+
+  /// Returns the horizontal and vertical shift of the chroma samples relative
+  /// to center-sited chroma, in pixels of the subsampled chroma planes of
+  /// `format`.
+  ///
+  /// For example, left-sited 4:2:0 chroma is shifted by -0.25 chroma pixels
+  /// (half a luma pixel) horizontally. Formats without subsampling in a
+  /// direction always have no shift in that direction.
+  #[must_use]
+  #[allow(clippy::cast_precision_loss)]
+  pub fn offsets<T: HoldsVideoFormat>(self, format: &T) -> (f32, f32) {
+    // Distance in chroma pixels between the first luma sample and the center
+    // of the luma samples covered by one chroma sample.
+    let shift = |sub_sampling: i32| {
+      let factor = (1 << sub_sampling) as f32;
+      (factor - 1.0) / (2.0 * factor)
+    };
+    let shift_w = shift(format.video_format().sub_sampling_w);
+    let shift_h = shift(format.video_format().sub_sampling_h);
+
+    let left = match self {
+      Self::Left | Self::TopLeft | Self::BottomLeft => -shift_w,
+      Self::Center | Self::Top | Self::Bottom => 0.0,
+    };
+    let top = match self {
+      Self::TopLeft | Self::Top => -shift_h,
+      Self::Left | Self::Center => 0.0,
+      Self::BottomLeft | Self::Bottom => shift_h,
+    };
+
+    (left, top)
+  }
+}
+
+/// Field order of a frame.
+#[derive(AsRefStr, Clone, Copy, Debug, EnumString, Eq, FromPrimitive, PartialEq, ToPrimitive)]
+#[strum(ascii_case_insensitive, serialize_all = "snake_case")]
+pub enum FieldBased {
+  /// Progressive frame.
+  Progressive = 0,
+
+  /// Interlaced, bottom field first.
+  Bff = 1,
+
+  /// Interlaced, top field first.
+  Tff = 2,
+}
+
+impl FieldBased {
+  /// Reads the field order from the `_FieldBased` property of a frame.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the property is missing or is not a valid field
+  /// order.
+  pub fn from_frame(frame: &VideoFrame) -> Result<Self, VapoursError> {
+    get_enum_prop(frame, key!(c"_FieldBased"))
+  }
+
+  /// Writes the field order to the `_FieldBased` property of a frame.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the property could not be set.
+  pub fn set_on_frame(self, frame: &mut VideoFrame) -> Result<(), VapoursError> {
+    set_int_prop(frame, key!(c"_FieldBased"), self as i64)
+  }
+
+  /// Whether this is an interlaced field order.
+  #[must_use]
+  pub const fn is_interlaced(self) -> bool {
+    !matches!(self, Self::Progressive)
+  }
+
+  /// Whether the top field comes first.
+  #[must_use]
+  pub const fn is_tff(self) -> bool {
+    matches!(self, Self::Tff)
+  }
+}
+
+/// Coarse resolution classes used to guess color properties, matching
+/// vs-tools.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Resolution {
+  /// Up to 1024x576.
+  Sd,
+
+  /// Up to 2048x1536.
+  Hd,
+
+  /// Anything larger.
+  Uhd,
+}
+
+impl Resolution {
+  const fn new(width: i32, height: i32) -> Self {
+    if width <= 1024 && height <= 576 {
+      Self::Sd
+    } else if width <= 2048 && height <= 1536 {
+      Self::Hd
+    } else {
+      Self::Uhd
+    }
+  }
+}
+
+/// Reads an integer frame property and converts it to an enum.
+fn get_enum_prop<T: FromPrimitive>(frame: &VideoFrame, key: &KeyStr) -> Result<T, VapoursError> {
+  T::from_i64(frame.get_prop(key)?).ok_or_else(|| {
+    VapoursError::FramePropertyError(key.to_string(), PropertyErrorReason::InvalidValue)
+  })
+}
+
+/// Writes an integer frame property, replacing any existing value.
+fn set_int_prop(frame: &mut VideoFrame, key: &KeyStr, value: i64) -> Result<(), VapoursError> {
+  frame.set_prop(key, &value)
+}
+
 #[cfg(test)]
 mod tests {
-  use num_traits::FromPrimitive;
+  use std::str::FromStr;
+
+  use approx::assert_relative_eq;
+  use rstest::rstest;
+  use vapoursynth4_rs::frame::VideoFormat;
+
+  use crate::vs_enums::{RGB24, YUV410P8, YUV420P10, YUV420P16, YUV420P8, YUV422P8, YUV444P8};
 
   use super::*;
 
@@ -25,4 +559,154 @@ mod tests {
     assert_eq!(ColorRange::from_u8(1), Some(ColorRange::Limited));
     assert_eq!(ColorRange::from_u8(2), None);
   }
+
+  #[test]
+  fn test_value_zimg() {
+    assert_eq!(ColorRange::Limited.value_zimg(), 0);
+    assert_eq!(ColorRange::Full.value_zimg(), 1);
+  }
+
+  #[test]
+  fn test_matrix_from_primitive() {
+    assert_eq!(Matrix::from_u8(0), Some(Matrix::Rgb));
+    assert_eq!(Matrix::from_u8(1), Some(Matrix::Bt709));
+    assert_eq!(Matrix::from_u8(3), None);
+    assert_eq!(Matrix::from_u8(9), Some(Matrix::Bt2020ncl));
+    assert_eq!(Matrix::from_u8(14), Some(Matrix::ICtCp));
+    assert_eq!(Matrix::from_u8(15), None);
+  }
+
+  #[rstest]
+  #[case("709", Matrix::Bt709)]
+  #[case("BT709", Matrix::Bt709)]
+  #[case("unspec", Matrix::Unspecified)]
+  #[case("170m", Matrix::Smpte170m)]
+  #[case("2020ncl", Matrix::Bt2020ncl)]
+  #[case("ICtCp", Matrix::ICtCp)]
+  fn test_matrix_from_str(#[case] input: &str, #[case] expected: Matrix) {
+    assert_eq!(Matrix::from_str(input), Ok(expected));
+  }
+
+  #[test]
+  fn test_matrix_as_ref() {
+    assert_eq!(Matrix::Bt709.as_ref(), "709");
+    assert_eq!(Matrix::Smpte170m.as_ref(), "170m");
+    assert_eq!(Matrix::Rgb.as_ref(), "rgb");
+  }
+
+  #[test]
+  fn test_transfer_from_primitive() {
+    assert_eq!(Transfer::from_u8(1), Some(Transfer::Bt709));
+    assert_eq!(Transfer::from_u8(3), None);
+    assert_eq!(Transfer::from_u8(12), None);
+    assert_eq!(Transfer::from_u8(16), Some(Transfer::St2084));
+    assert_eq!(Transfer::from_u8(17), None);
+    assert_eq!(Transfer::from_u8(18), Some(Transfer::AribB67));
+  }
+
+  #[rstest]
+  #[case("709", Transfer::Bt709)]
+  #[case("srgb", Transfer::Iec61966_2_1)]
+  #[case("PQ", Transfer::St2084)]
+  #[case("std-b67", Transfer::AribB67)]
+  fn test_transfer_from_str(#[case] input: &str, #[case] expected: Transfer) {
+    assert_eq!(Transfer::from_str(input), Ok(expected));
+  }
+
+  #[rstest]
+  #[case(YUV420P8, 640, 480, Transfer::Bt601)]
+  #[case(YUV420P8, 720, 576, Transfer::Bt601)]
+  #[case(YUV420P8, 1280, 720, Transfer::Bt709)]
+  #[case(YUV420P10, 1920, 1080, Transfer::Bt709)]
+  #[case(YUV420P10, 3840, 2160, Transfer::Bt2020_10)]
+  #[case(YUV420P16, 3840, 2160, Transfer::Bt2020_12)]
+  #[case(RGB24, 1920, 1080, Transfer::Iec61966_2_1)]
+  fn test_transfer_from_res(
+    #[case] format: VideoFormat,
+    #[case] width: i32,
+    #[case] height: i32,
+    #[case] expected: Transfer,
+  ) {
+    assert_eq!(Transfer::from_res(&format, width, height), expected);
+    assert_eq!(Transfer::from_video(&format, width, height, None), expected);
+  }
+
+  #[test]
+  fn test_primaries_from_primitive() {
+    assert_eq!(Primaries::from_u8(1), Some(Primaries::Bt709));
+    assert_eq!(Primaries::from_u8(9), Some(Primaries::Bt2020));
+    assert_eq!(Primaries::from_u8(13), None);
+    assert_eq!(Primaries::from_u8(22), Some(Primaries::Ebu3213e));
+  }
+
+  #[rstest]
+  #[case("709", Primaries::Bt709)]
+  #[case("DCI-P3", Primaries::St431_2)]
+  #[case("jedec-p22", Primaries::Ebu3213e)]
+  fn test_primaries_from_str(#[case] input: &str, #[case] expected: Primaries) {
+    assert_eq!(Primaries::from_str(input), Ok(expected));
+  }
+
+  #[rstest]
+  #[case(YUV420P8, 640, 480, Primaries::Smpte170m)]
+  #[case(YUV420P8, 720, 576, Primaries::Bt470bg)]
+  #[case(YUV420P8, 1920, 1080, Primaries::Bt709)]
+  #[case(YUV420P10, 3840, 2160, Primaries::Bt2020)]
+  #[case(RGB24, 3840, 2160, Primaries::Bt709)]
+  fn test_primaries_from_res(
+    #[case] format: VideoFormat,
+    #[case] width: i32,
+    #[case] height: i32,
+    #[case] expected: Primaries,
+  ) {
+    assert_eq!(Primaries::from_res(&format, width, height), expected);
+    assert_eq!(
+      Primaries::from_video(&format, width, height, None),
+      expected
+    );
+  }
+
+  #[test]
+  fn test_chroma_location_from_str() {
+    assert_eq!(
+      ChromaLocation::from_str("top_left"),
+      Ok(ChromaLocation::TopLeft)
+    );
+    assert_eq!(ChromaLocation::BottomLeft.as_ref(), "bottom_left");
+    assert_eq!(ChromaLocation::from_u8(5), Some(ChromaLocation::Bottom));
+    assert_eq!(ChromaLocation::from_u8(6), None);
+  }
+
+  #[rstest]
+  #[case(YUV420P8, ChromaLocation::Left, (-0.25, 0.0))]
+  #[case(YUV420P8, ChromaLocation::Center, (0.0, 0.0))]
+  #[case(YUV420P8, ChromaLocation::TopLeft, (-0.25, -0.25))]
+  #[case(YUV420P8, ChromaLocation::Top, (0.0, -0.25))]
+  #[case(YUV420P8, ChromaLocation::BottomLeft, (-0.25, 0.25))]
+  #[case(YUV420P8, ChromaLocation::Bottom, (0.0, 0.25))]
+  #[case(YUV422P8, ChromaLocation::TopLeft, (-0.25, 0.0))]
+  #[case(YUV410P8, ChromaLocation::TopLeft, (-0.375, -0.375))]
+  #[case(YUV444P8, ChromaLocation::TopLeft, (0.0, 0.0))]
+  fn test_chroma_location_offsets(
+    #[case] format: VideoFormat,
+    #[case] location: ChromaLocation,
+    #[case] expected: (f32, f32),
+  ) {
+    let (left, top) = location.offsets(&format);
+    assert_relative_eq!(left, expected.0);
+    assert_relative_eq!(top, expected.1);
+  }
+
+  #[test]
+  fn test_field_based() {
+    assert_eq!(FieldBased::from_u8(0), Some(FieldBased::Progressive));
+    assert_eq!(FieldBased::from_u8(1), Some(FieldBased::Bff));
+    assert_eq!(FieldBased::from_u8(2), Some(FieldBased::Tff));
+    assert_eq!(FieldBased::from_u8(3), None);
+    assert_eq!(FieldBased::from_str("TFF"), Ok(FieldBased::Tff));
+    assert!(!FieldBased::Progressive.is_interlaced());
+    assert!(FieldBased::Bff.is_interlaced());
+    assert!(!FieldBased::Bff.is_tff());
+    assert!(FieldBased::Tff.is_tff());
+  }
 }
@@ -4,6 +4,9 @@ use std::string::String;
 
 use miette::Diagnostic;
 use thiserror::Error;
+use vapoursynth4_rs::{map::MapPropertyError, ColorFamily, SampleType};
+
+use crate::utils::DitherType;
 
 /// Errors from vapours.
 #[derive(Debug, Diagnostic, Error)]
@@ -13,6 +16,124 @@ pub enum VapoursError {
   DependencyNotFoundError(String),
 
   /// Frame property error.
-  #[error("Error while trying to access frame property '{0}'.")]
-  FramePropertyError(String),
+  #[error("Error while trying to access frame property '{0}': {1}.")]
+  FramePropertyError(String, PropertyErrorReason),
+
+  /// A plugin function returned an error.
+  #[error("Error while invoking '{0}': {1}")]
+  InvocationError(String, String),
+
+  /// The requested dither type cannot be honoured by the chosen backend.
+  #[error("Dither type '{0:?}' is not supported by '{1}'.")]
+  UnsupportedDitherError(DitherType, String),
+
+  /// A pixel type does not match the format of a frame or clip.
+  #[error("Pixel type '{0}' does not match a format with {1} bytes per sample of type {2:?}.")]
+  PixelTypeMismatchError(&'static str, i32, SampleType),
+
+  /// The requested video format is invalid or unsupported.
+  #[error("Unsupported video format: {0}.")]
+  UnsupportedFormatError(String),
+
+  /// A `planes` argument could not be normalized.
+  #[error("Invalid planes: {0}.")]
+  InvalidPlanesError(String),
+
+  /// A clip passed to a filter does not meet its requirements.
+  #[error("Clip '{1}' passed to '{0}' is not supported: {2}.")]
+  InvalidClipError(String, String, ClipErrorReason),
+}
+
+/// Why accessing a frame property failed.
+#[derive(Clone, Copy, Debug, Eq, Error, PartialEq)]
+pub enum PropertyErrorReason {
+  /// The property does not exist.
+  #[error("property is missing")]
+  Missing,
+
+  /// The property exists but holds a different type.
+  #[error("property has the wrong type")]
+  WrongType,
+
+  /// The requested index is past the number of elements of the property.
+  #[error("index out of range")]
+  IndexOutOfRange,
+
+  /// The property holds a value that is not valid for its meaning.
+  #[error("property has an invalid value")]
+  InvalidValue,
+
+  /// The property map could not be accessed or is in an error state.
+  #[error("property map is unavailable")]
+  Unavailable,
+}
+
+/// Why a clip does not meet the requirements of a filter.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum ClipErrorReason {
+  /// The format of the clip can vary between frames.
+  #[error("format is not constant")]
+  VariableFormat,
+
+  /// The resolution of the clip can vary between frames.
+  #[error("resolution is not constant")]
+  VariableResolution,
+
+  /// The format of the clip differs from that of another clip, named last.
+  #[error("format {0} does not match format {1} of clip '{2}'")]
+  FormatMismatch(String, String, String),
+
+  /// The color family of the clip is not one of the allowed ones.
+  #[error("color family {0:?} is not one of {1:?}")]
+  UnsupportedColorFamily(ColorFamily, Vec<ColorFamily>),
+
+  /// The bit depth of the clip is not one of the allowed ones.
+  #[error("bit depth {0} is not one of {1:?}")]
+  UnsupportedDepth(i32, Vec<i32>),
+
+  /// The width or height of the clip is not a multiple of the given number.
+  #[error("resolution {0}x{1} is not mod {2}")]
+  InvalidResolution(i32, i32, u32),
+}
+
+impl From<MapPropertyError> for PropertyErrorReason {
+  fn from(error: MapPropertyError) -> Self {
+    match error {
+      MapPropertyError::KeyNotFound => Self::Missing,
+      MapPropertyError::InvalidType => Self::WrongType,
+      MapPropertyError::IndexOutOfBound => Self::IndexOutOfRange,
+      MapPropertyError::MapError => Self::Unavailable,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rstest::rstest;
+
+  use super::*;
+
+  #[rstest]
+  #[case(MapPropertyError::KeyNotFound, PropertyErrorReason::Missing)]
+  #[case(MapPropertyError::InvalidType, PropertyErrorReason::WrongType)]
+  #[case(
+    MapPropertyError::IndexOutOfBound,
+    PropertyErrorReason::IndexOutOfRange
+  )]
+  #[case(MapPropertyError::MapError, PropertyErrorReason::Unavailable)]
+  fn test_property_error_reason_from(
+    #[case] error: MapPropertyError,
+    #[case] expected: PropertyErrorReason,
+  ) {
+    assert_eq!(PropertyErrorReason::from(error), expected);
+  }
+
+  #[test]
+  fn test_frame_property_error_message() {
+    assert_eq!(
+      VapoursError::FramePropertyError("_Matrix".to_string(), PropertyErrorReason::InvalidValue)
+        .to_string(),
+      "Error while trying to access frame property '_Matrix': property has an invalid value."
+    );
+  }
 }
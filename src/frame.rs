@@ -12,15 +12,236 @@
 //! | 16        | [`SampleType::Integer`] | [`u16`] |
 //! | 32        | [`SampleType::Float`]   | [`f32`] |
 //!
-//! [`SampleType::Integer`]: vapoursynth4_rs::SampleType::Integer
-//! [`SampleType::Float`]: vapoursynth4_rs::SampleType::Float
+//! The `try_` methods of [`VapoursVideoFrame`] take a [`Pixel`] instead and
+//! check it against the frame's format.
 
 use core::slice;
-use std::{iter::FusedIterator, marker::PhantomData, ops::Range};
+use std::{
+  iter::{FusedIterator, Take, Zip},
+  marker::PhantomData,
+  ops::{Index, IndexMut, Range},
+  slice::{Chunks, ChunksMut},
+};
 
-use vapoursynth4_rs::frame::VideoFrame;
+use vapoursynth4_rs::{frame::VideoFrame, SampleType};
 
-use crate::generic::HoldsVideoFormat;
+use crate::{errors::VapoursError, generic::HoldsVideoFormat};
+
+/// A pixel type that corresponds to a sample type and size of a video format.
+pub trait Pixel: Copy + Default + PartialOrd + Send + Sync + 'static {
+  /// The sample type this pixel type is used for.
+  const SAMPLE_TYPE: SampleType;
+
+  /// Size of this pixel type in bytes.
+  const BYTES_PER_SAMPLE: i32;
+
+  /// Whether this pixel type matches the sample type and bytes per sample of
+  /// the given format.
+  #[must_use]
+  fn matches_format<F: HoldsVideoFormat>(format: &F) -> bool {
+    format.sample_type() == Self::SAMPLE_TYPE
+      && format.video_format().bytes_per_sample == Self::BYTES_PER_SAMPLE
+  }
+
+  /// Converts this pixel to a [`f32`].
+  #[must_use]
+  fn to_f32(self) -> f32;
+
+  /// Converts a [`f32`] to this pixel type. Integer types truncate the value
+  /// and saturate at their bounds, so values should be rounded beforehand.
+  #[must_use]
+  fn from_f32(value: f32) -> Self;
+
+  /// Returns an error if this pixel type does not match the given format.
+  ///
+  /// # Errors
+  ///
+  /// Returns [`VapoursError::PixelTypeMismatchError`] if the sample type or
+  /// bytes per sample differ.
+  fn check_format<F: HoldsVideoFormat>(format: &F) -> Result<(), VapoursError> {
+    if Self::matches_format(format) {
+      Ok(())
+    } else {
+      Err(VapoursError::PixelTypeMismatchError(
+        std::any::type_name::<Self>(),
+        format.video_format().bytes_per_sample,
+        format.sample_type(),
+      ))
+    }
+  }
+}
+
+impl Pixel for u8 {
+  const SAMPLE_TYPE: SampleType = SampleType::Integer;
+  const BYTES_PER_SAMPLE: i32 = 1;
+
+  #[inline]
+  fn to_f32(self) -> f32 {
+    f32::from(self)
+  }
+
+  #[inline]
+  fn from_f32(value: f32) -> Self {
+    value as Self
+  }
+}
+
+impl Pixel for u16 {
+  const SAMPLE_TYPE: SampleType = SampleType::Integer;
+  const BYTES_PER_SAMPLE: i32 = 2;
+
+  #[inline]
+  fn to_f32(self) -> f32 {
+    f32::from(self)
+  }
+
+  #[inline]
+  fn from_f32(value: f32) -> Self {
+    value as Self
+  }
+}
+
+impl Pixel for u32 {
+  const SAMPLE_TYPE: SampleType = SampleType::Integer;
+  const BYTES_PER_SAMPLE: i32 = 4;
+
+  #[inline]
+  #[allow(clippy::cast_precision_loss)]
+  fn to_f32(self) -> f32 {
+    self as f32
+  }
+
+  #[inline]
+  fn from_f32(value: f32) -> Self {
+    value as Self
+  }
+}
+
+#[cfg(feature = "half")]
+impl Pixel for half::f16 {
+  const SAMPLE_TYPE: SampleType = SampleType::Float;
+  const BYTES_PER_SAMPLE: i32 = 2;
+
+  #[inline]
+  fn to_f32(self) -> f32 {
+    self.to_f32()
+  }
+
+  #[inline]
+  fn from_f32(value: f32) -> Self {
+    Self::from_f32(value)
+  }
+}
+
+impl Pixel for f32 {
+  const SAMPLE_TYPE: SampleType = SampleType::Float;
+  const BYTES_PER_SAMPLE: i32 = 4;
+
+  #[inline]
+  fn to_f32(self) -> f32 {
+    self
+  }
+
+  #[inline]
+  fn from_f32(value: f32) -> Self {
+    value
+  }
+}
+
+/// A [`Pixel`] of an integer format, whose value can index a table.
+pub trait IntegerPixel: Pixel {
+  /// Converts this pixel to a [`usize`].
+  #[must_use]
+  fn to_usize(self) -> usize;
+}
+
+impl IntegerPixel for u8 {
+  #[inline]
+  fn to_usize(self) -> usize {
+    usize::from(self)
+  }
+}
+
+impl IntegerPixel for u16 {
+  #[inline]
+  fn to_usize(self) -> usize {
+    usize::from(self)
+  }
+}
+
+impl IntegerPixel for u32 {
+  #[inline]
+  fn to_usize(self) -> usize {
+    self as usize
+  }
+}
+
+/// A computation that is generic over the pixel type, to be run with the
+/// pixel type matching a format at runtime by [`dispatch`].
+///
+/// ```
+/// # use std::iter::zip;
+/// #
+/// # use vapours::{
+/// #   errors::VapoursError,
+/// #   frame::{dispatch, Pixel, PixelVisitor, PlaneView, PlaneViewMut, VapoursVideoFrame},
+/// # };
+/// # use vapoursynth4_rs::frame::VideoFrame;
+/// #
+/// # fn invert_plane<T: Pixel>(src: &PlaneView<'_, T>, dst: &mut PlaneViewMut<'_, T>) {}
+/// #
+/// struct Invert<'a>(&'a VideoFrame, &'a mut VideoFrame);
+///
+/// impl PixelVisitor for Invert<'_> {
+///   type Output = ();
+///
+///   fn visit<T: Pixel>(self) {
+///     for (src, mut dst) in zip(self.0.planes_iter::<T>(), self.1.planes_iter_mut::<T>()) {
+///       invert_plane(&src, &mut dst);
+///     }
+///   }
+/// }
+///
+/// # fn get_frame(src: &VideoFrame, dst: &mut VideoFrame) -> Result<(), VapoursError> {
+/// dispatch(src, Invert(src, dst))?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait PixelVisitor {
+  /// The result of the computation.
+  type Output;
+
+  /// Runs the computation with the pixel type `T`.
+  fn visit<T: Pixel>(self) -> Self::Output;
+}
+
+/// Runs `visitor` with the pixel type matching `format`.
+///
+/// Integer formats use [`u8`], [`u16`] or [`u32`] and float formats use
+/// [`f32`], or `half::f16` with the `half` feature, depending on their bytes
+/// per sample.
+///
+/// # Errors
+///
+/// Returns an error if no pixel type matches the format, e.g. for 16-bit
+/// float formats without the `half` feature.
+pub fn dispatch<F, V>(format: &F, visitor: V) -> Result<V::Output, VapoursError>
+where
+  F: HoldsVideoFormat,
+  V: PixelVisitor,
+{
+  match (format.sample_type(), format.video_format().bytes_per_sample) {
+    (SampleType::Integer, 1) => Ok(visitor.visit::<u8>()),
+    (SampleType::Integer, 2) => Ok(visitor.visit::<u16>()),
+    (SampleType::Integer, 4) => Ok(visitor.visit::<u32>()),
+    #[cfg(feature = "half")]
+    (SampleType::Float, 2) => Ok(visitor.visit::<half::f16>()),
+    (SampleType::Float, 4) => Ok(visitor.visit::<f32>()),
+    (sample_type, bytes_per_sample) => Err(VapoursError::UnsupportedFormatError(format!(
+      "no pixel type for {sample_type:?} samples of {bytes_per_sample} bytes"
+    ))),
+  }
+}
 
 /// [`VideoFrame`] extensions.
 pub trait VapoursVideoFrame {
@@ -45,6 +266,42 @@ pub trait VapoursVideoFrame {
   /// [module-level documentation](self) for more information about the pixel
   /// type `T`.
   fn planes_iter_mut<T>(&mut self) -> PlanesIterMut<'_, T>;
+
+  /// Returns the video frame's data as a slice, checking that `T` matches the
+  /// frame's format.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if `T` does not match the frame's sample type or bytes
+  /// per sample.
+  fn try_as_slice<T: Pixel>(&self, plane: i32) -> Result<&[T], VapoursError>;
+
+  /// Returns the video frame's data as a mutable slice, checking that `T`
+  /// matches the frame's format.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if `T` does not match the frame's sample type or bytes
+  /// per sample.
+  fn try_as_mut_slice<T: Pixel>(&mut self, plane: i32) -> Result<&mut [T], VapoursError>;
+
+  /// Returns an iterator over the planes of the video frame, checking that `T`
+  /// matches the frame's format.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if `T` does not match the frame's sample type or bytes
+  /// per sample.
+  fn try_planes_iter<T: Pixel>(&self) -> Result<PlanesIter<'_, T>, VapoursError>;
+
+  /// Returns an iterator over the mutable planes of the video frame, checking
+  /// that `T` matches the frame's format.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if `T` does not match the frame's sample type or bytes
+  /// per sample.
+  fn try_planes_iter_mut<T: Pixel>(&mut self) -> Result<PlanesIterMut<'_, T>, VapoursError>;
 }
 
 impl VapoursVideoFrame for VideoFrame {
@@ -73,6 +330,30 @@ impl VapoursVideoFrame for VideoFrame {
   fn planes_iter_mut<T>(&mut self) -> PlanesIterMut<'_, T> {
     PlanesIterMut::new(self)
   }
+
+  #[inline]
+  fn try_as_slice<T: Pixel>(&self, plane: i32) -> Result<&[T], VapoursError> {
+    T::check_format(self)?;
+    Ok(self.as_slice(plane))
+  }
+
+  #[inline]
+  fn try_as_mut_slice<T: Pixel>(&mut self, plane: i32) -> Result<&mut [T], VapoursError> {
+    T::check_format(self)?;
+    Ok(self.as_mut_slice(plane))
+  }
+
+  #[inline]
+  fn try_planes_iter<T: Pixel>(&self) -> Result<PlanesIter<'_, T>, VapoursError> {
+    T::check_format(self)?;
+    Ok(self.planes_iter())
+  }
+
+  #[inline]
+  fn try_planes_iter_mut<T: Pixel>(&mut self) -> Result<PlanesIterMut<'_, T>, VapoursError> {
+    T::check_format(self)?;
+    Ok(self.planes_iter_mut())
+  }
 }
 
 /// A plane view.
@@ -91,6 +372,73 @@ pub struct PlaneView<'a, T> {
   pub stride: isize,
 }
 
+impl<'a, T> PlaneView<'a, T> {
+  /// Returns the pixel at column `x` and line `y`, or [`None`] if it lies
+  /// outside of the plane.
+  #[must_use]
+  pub fn get(&self, x: usize, y: usize) -> Option<&'a T> {
+    pixel_index::<T>(x, y, self.width, self.height, self.stride).and_then(|i| self.data.get(i))
+  }
+
+  /// Returns the pixel at column `x` and line `y`, handling coordinates
+  /// outside of the plane according to `mode`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the plane is empty, as it has no pixel to return.
+  #[must_use]
+  pub fn get_edge(&self, x: isize, y: isize, mode: EdgeMode) -> &'a T {
+    let (x, y) = mode.resolve(x, y, self.width, self.height);
+    &self.data[y * stride_in_elements::<T>(self.stride) + x]
+  }
+
+  /// Returns an iterator over the lines of the plane. Each line holds exactly
+  /// `width` pixels, excluding any padding.
+  pub fn rows(&self) -> Rows<'a, T> {
+    Rows::new(self.data, self.width, self.height, self.stride)
+  }
+
+  /// Returns a view of the top field, i.e. the even lines of the plane.
+  #[must_use]
+  pub const fn top_field(&self) -> Self {
+    Self {
+      data: self.data,
+      height: (self.height + 1) / 2,
+      width: self.width,
+      stride: self.stride * 2,
+    }
+  }
+
+  /// Returns a view of the bottom field, i.e. the odd lines of the plane.
+  #[must_use]
+  pub fn bottom_field(&self) -> Self {
+    let offset = stride_in_elements::<T>(self.stride);
+    Self {
+      data: &self.data[offset.min(self.data.len())..],
+      height: self.height / 2,
+      width: self.width,
+      stride: self.stride * 2,
+    }
+  }
+}
+
+impl<T> Index<(usize, usize)> for PlaneView<'_, T> {
+  type Output = T;
+
+  /// Returns the pixel at `(x, y)`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the pixel lies outside of the plane.
+  #[inline]
+  fn index(&self, (x, y): (usize, usize)) -> &T {
+    let (width, height) = (self.width, self.height);
+    self
+      .get(x, y)
+      .unwrap_or_else(|| panic!("pixel ({x}, {y}) out of bounds for a {width}x{height} plane"))
+  }
+}
+
 /// An iterator that yields the plane data of a [`VideoFrame`] along with their
 /// dimensions.
 ///
@@ -190,6 +538,104 @@ pub struct PlaneViewMut<'a, T> {
   pub stride: isize,
 }
 
+impl<T> PlaneViewMut<'_, T> {
+  /// Returns the pixel at column `x` and line `y`, or [`None`] if it lies
+  /// outside of the plane.
+  #[must_use]
+  pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+    pixel_index::<T>(x, y, self.width, self.height, self.stride).and_then(|i| self.data.get(i))
+  }
+
+  /// Returns the mutable pixel at column `x` and line `y`, or [`None`] if it
+  /// lies outside of the plane.
+  #[must_use]
+  pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+    pixel_index::<T>(x, y, self.width, self.height, self.stride).and_then(|i| self.data.get_mut(i))
+  }
+
+  /// Returns the pixel at column `x` and line `y`, handling coordinates
+  /// outside of the plane according to `mode`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the plane is empty, as it has no pixel to return.
+  #[must_use]
+  pub fn get_edge(&self, x: isize, y: isize, mode: EdgeMode) -> &T {
+    let (x, y) = mode.resolve(x, y, self.width, self.height);
+    &self.data[y * stride_in_elements::<T>(self.stride) + x]
+  }
+
+  /// Returns an iterator over the lines of the plane. Each line holds exactly
+  /// `width` pixels, excluding any padding.
+  pub fn rows(&self) -> Rows<'_, T> {
+    Rows::new(self.data, self.width, self.height, self.stride)
+  }
+
+  /// Returns an iterator over the mutable lines of the plane. Each line holds
+  /// exactly `width` pixels, excluding any padding.
+  pub fn rows_mut(&mut self) -> RowsMut<'_, T> {
+    RowsMut::new(self.data, self.width, self.height, self.stride)
+  }
+
+  /// Returns a mutable view of the top field, i.e. the even lines of the
+  /// plane.
+  #[must_use]
+  pub const fn top_field_mut(&mut self) -> PlaneViewMut<'_, T> {
+    PlaneViewMut {
+      data: self.data,
+      height: (self.height + 1) / 2,
+      width: self.width,
+      stride: self.stride * 2,
+    }
+  }
+
+  /// Returns a mutable view of the bottom field, i.e. the odd lines of the
+  /// plane.
+  #[must_use]
+  pub fn bottom_field_mut(&mut self) -> PlaneViewMut<'_, T> {
+    let offset = stride_in_elements::<T>(self.stride);
+    let len = self.data.len();
+    PlaneViewMut {
+      data: &mut self.data[offset.min(len)..],
+      height: self.height / 2,
+      width: self.width,
+      stride: self.stride * 2,
+    }
+  }
+}
+
+impl<T> Index<(usize, usize)> for PlaneViewMut<'_, T> {
+  type Output = T;
+
+  /// Returns the pixel at `(x, y)`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the pixel lies outside of the plane.
+  #[inline]
+  fn index(&self, (x, y): (usize, usize)) -> &T {
+    let (width, height) = (self.width, self.height);
+    self
+      .get(x, y)
+      .unwrap_or_else(|| panic!("pixel ({x}, {y}) out of bounds for a {width}x{height} plane"))
+  }
+}
+
+impl<T> IndexMut<(usize, usize)> for PlaneViewMut<'_, T> {
+  /// Returns the mutable pixel at `(x, y)`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the pixel lies outside of the plane.
+  #[inline]
+  fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+    let (width, height) = (self.width, self.height);
+    self
+      .get_mut(x, y)
+      .unwrap_or_else(|| panic!("pixel ({x}, {y}) out of bounds for a {width}x{height} plane"))
+  }
+}
+
 /// An iterator that yields the mutable plane data of a [`VideoFrame`] along
 /// with their dimensions.
 ///
@@ -277,3 +723,451 @@ impl<T> ExactSizeIterator for PlanesIterMut<'_, T> {
 }
 
 impl<T> FusedIterator for PlanesIterMut<'_, T> {}
+
+/// How to handle pixel coordinates that lie outside of a plane.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EdgeMode {
+  /// Use the nearest edge pixel, e.g. `-2, -1 | 0, 1, 2` reads `0, 0 | 0, 1,
+  /// 2`.
+  Clamp,
+
+  /// Mirror around the edge pixel without repeating it, e.g. `-2, -1 | 0, 1,
+  /// 2` reads `2, 1 | 0, 1, 2`.
+  Mirror,
+}
+
+impl EdgeMode {
+  /// Maps possibly out of bounds coordinates to coordinates inside a plane of
+  /// the given dimensions.
+  fn resolve(self, x: isize, y: isize, width: i32, height: i32) -> (usize, usize) {
+    assert!(
+      width > 0 && height > 0,
+      "cannot resolve ({x}, {y}) in an empty {width}x{height} plane"
+    );
+    (
+      self.resolve_one(x, width as isize),
+      self.resolve_one(y, height as isize),
+    )
+  }
+
+  fn resolve_one(self, n: isize, len: isize) -> usize {
+    match self {
+      Self::Clamp => n.clamp(0, len - 1) as usize,
+      Self::Mirror if len == 1 => 0,
+      Self::Mirror => {
+        let period = 2 * (len - 1);
+        let n = n.rem_euclid(period);
+        (if n < len { n } else { period - n }) as usize
+      }
+    }
+  }
+}
+
+/// Index into the data of a plane of the pixel at `(x, y)`, or [`None`] if it
+/// lies outside of the plane.
+const fn pixel_index<T>(
+  x: usize,
+  y: usize,
+  width: i32,
+  height: i32,
+  stride: isize,
+) -> Option<usize> {
+  if x < width as usize && y < height as usize {
+    Some(y * stride_in_elements::<T>(stride) + x)
+  } else {
+    None
+  }
+}
+
+/// Number of elements of type `T` in `stride` bytes.
+const fn stride_in_elements<T>(stride: isize) -> usize {
+  (stride / size_of::<T>() as isize) as usize
+}
+
+/// An iterator over the lines of a plane, excluding padding.
+///
+/// This struct is created by the [`rows`](PlaneView::rows) method.
+#[derive(Clone, Debug)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Rows<'a, T> {
+  chunks: Take<Chunks<'a, T>>,
+  width: usize,
+}
+
+impl<'a, T> Rows<'a, T> {
+  fn new(data: &'a [T], width: i32, height: i32, stride: isize) -> Self {
+    Self {
+      chunks: data
+        .chunks(stride_in_elements::<T>(stride).max(1))
+        .take(height as usize),
+      width: width as usize,
+    }
+  }
+}
+
+impl<'a, T> Iterator for Rows<'a, T> {
+  type Item = &'a [T];
+
+  #[inline]
+  fn next(&mut self) -> Option<&'a [T]> {
+    self.chunks.next().map(|row| &row[..self.width])
+  }
+
+  #[inline]
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.chunks.size_hint()
+  }
+}
+
+impl<T> DoubleEndedIterator for Rows<'_, T> {
+  #[inline]
+  fn next_back(&mut self) -> Option<Self::Item> {
+    self.chunks.next_back().map(|row| &row[..self.width])
+  }
+}
+
+impl<T> ExactSizeIterator for Rows<'_, T> {}
+
+impl<T> FusedIterator for Rows<'_, T> {}
+
+/// An iterator over the mutable lines of a plane, excluding padding.
+///
+/// This struct is created by the [`rows_mut`](PlaneViewMut::rows_mut) method.
+#[derive(Debug)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct RowsMut<'a, T> {
+  chunks: Take<ChunksMut<'a, T>>,
+  width: usize,
+}
+
+impl<'a, T> RowsMut<'a, T> {
+  fn new(data: &'a mut [T], width: i32, height: i32, stride: isize) -> Self {
+    Self {
+      chunks: data
+        .chunks_mut(stride_in_elements::<T>(stride).max(1))
+        .take(height as usize),
+      width: width as usize,
+    }
+  }
+}
+
+impl<'a, T> Iterator for RowsMut<'a, T> {
+  type Item = &'a mut [T];
+
+  #[inline]
+  fn next(&mut self) -> Option<&'a mut [T]> {
+    self.chunks.next().map(|row| &mut row[..self.width])
+  }
+
+  #[inline]
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.chunks.size_hint()
+  }
+}
+
+impl<T> DoubleEndedIterator for RowsMut<'_, T> {
+  #[inline]
+  fn next_back(&mut self) -> Option<Self::Item> {
+    self.chunks.next_back().map(|row| &mut row[..self.width])
+  }
+}
+
+impl<T> ExactSizeIterator for RowsMut<'_, T> {}
+
+impl<T> FusedIterator for RowsMut<'_, T> {}
+
+/// Iterates over the lines of a source and a destination plane in lockstep.
+/// The planes may have different strides and pixel types but should have the
+/// same dimensions.
+///
+/// ```
+/// use std::iter::zip;
+///
+/// use vapours::frame::{zip_rows, PlaneView, PlaneViewMut};
+///
+/// let src_data = [1u8, 2, 0, 3, 4, 0];
+/// let src_plane = PlaneView {
+///   data: &src_data,
+///   height: 2,
+///   width: 2,
+///   stride: 3,
+/// };
+/// let mut dst_data = [0u8; 4];
+/// let mut dst_plane = PlaneViewMut {
+///   data: &mut dst_data,
+///   height: 2,
+///   width: 2,
+///   stride: 2,
+/// };
+///
+/// for (src_row, dst_row) in zip_rows(&src_plane, &mut dst_plane) {
+///   for (src_pixel, dst_pixel) in zip(src_row, dst_row) {
+///     *dst_pixel = !*src_pixel;
+///   }
+/// }
+/// assert_eq!(dst_data, [254, 253, 252, 251]);
+/// ```
+pub fn zip_rows<'a, 'b, S, D>(
+  src: &PlaneView<'a, S>,
+  dst: &'b mut PlaneViewMut<'_, D>,
+) -> Zip<Rows<'a, S>, RowsMut<'b, D>> {
+  debug_assert_eq!(src.width, dst.width);
+  debug_assert_eq!(src.height, dst.height);
+  src.rows().zip(dst.rows_mut())
+}
+
+#[cfg(test)]
+mod tests {
+  use rstest::rstest;
+  use vapoursynth4_rs::frame::VideoFormat;
+
+  use crate::vs_enums::{GRAY16, GRAY32, GRAY8, GRAYH, GRAYS, YUV420P10, YUV444PS};
+
+  use super::*;
+
+  #[rustfmt::skip]
+  const DATA: [u16; 20] = [
+    0, 1, 2, 3,
+    10, 11, 12, 13,
+    20, 21, 22, 23,
+    30, 31, 32, 33,
+    40, 41, 42, 43,
+  ];
+
+  const fn view(data: &[u16]) -> PlaneView<'_, u16> {
+    PlaneView {
+      data,
+      height: 5,
+      width: 3,
+      stride: 8,
+    }
+  }
+
+  #[test]
+  fn test_fields() {
+    let plane = view(&DATA);
+
+    let top = plane.top_field();
+    assert_eq!(top.height, 3);
+    assert_eq!(top.width, 3);
+    assert_eq!(top.stride, 16);
+    assert_eq!(top.data[0], 0);
+    assert_eq!(top.data[8], 20);
+
+    let bottom = plane.bottom_field();
+    assert_eq!(bottom.height, 2);
+    assert_eq!(bottom.stride, 16);
+    assert_eq!(bottom.data[0], 10);
+    assert_eq!(bottom.data[8], 30);
+  }
+
+  #[test]
+  fn test_fields_mut() {
+    let mut data = DATA;
+    let mut plane = PlaneViewMut {
+      data: &mut data,
+      height: 5,
+      width: 3,
+      stride: 8,
+    };
+
+    plane.bottom_field_mut().data[8] = 99;
+    assert_eq!(plane.top_field_mut().height, 3);
+    assert_eq!(data[12], 99);
+  }
+
+  #[test]
+  fn test_rows() {
+    let plane = view(&DATA);
+    let rows: Vec<&[u16]> = plane.rows().collect();
+    assert_eq!(
+      rows,
+      [
+        &[0, 1, 2][..],
+        &[10, 11, 12],
+        &[20, 21, 22],
+        &[30, 31, 32],
+        &[40, 41, 42],
+      ]
+    );
+    assert_eq!(plane.rows().len(), 5);
+    assert_eq!(plane.rows().next_back(), Some(&[40, 41, 42][..]));
+
+    let bottom: Vec<&[u16]> = plane.bottom_field().rows().collect();
+    assert_eq!(bottom, [&[10, 11, 12][..], &[30, 31, 32]]);
+  }
+
+  #[test]
+  fn test_zip_rows() {
+    let src = view(&DATA);
+    let mut data = [0u8; 15];
+    let mut dst = PlaneViewMut {
+      data: &mut data,
+      height: 5,
+      width: 3,
+      stride: 3,
+    };
+
+    for (src_row, dst_row) in zip_rows(&src, &mut dst) {
+      for (src_pixel, dst_pixel) in src_row.iter().zip(dst_row) {
+        *dst_pixel = *src_pixel as u8;
+      }
+    }
+
+    assert_eq!(
+      data,
+      [0, 1, 2, 10, 11, 12, 20, 21, 22, 30, 31, 32, 40, 41, 42]
+    );
+  }
+
+  #[test]
+  fn test_get() {
+    let plane = view(&DATA);
+    assert_eq!(plane.get(0, 0), Some(&0));
+    assert_eq!(plane.get(2, 4), Some(&42));
+    assert_eq!(plane.get(3, 0), None);
+    assert_eq!(plane.get(0, 5), None);
+    assert_eq!(plane[(1, 2)], 21);
+  }
+
+  #[test]
+  #[should_panic(expected = "pixel (3, 1) out of bounds for a 3x5 plane")]
+  fn test_index_padding() {
+    let plane = view(&DATA);
+    let _ = plane[(3, 1)];
+  }
+
+  #[test]
+  fn test_get_mut() {
+    let mut data = DATA;
+    let mut plane = PlaneViewMut {
+      data: &mut data,
+      height: 5,
+      width: 3,
+      stride: 8,
+    };
+
+    *plane.get_mut(1, 1).unwrap() = 99;
+    plane[(2, 3)] = 98;
+    assert_eq!(plane.get_mut(3, 3), None);
+    assert_eq!(plane[(1, 1)], 99);
+    assert_eq!(plane.get_edge(5, 3, EdgeMode::Clamp), &98);
+    assert_eq!(data[5], 99);
+    assert_eq!(data[14], 98);
+  }
+
+  #[rstest]
+  #[case(-1, -1, EdgeMode::Clamp, 0)]
+  #[case(-5, 2, EdgeMode::Clamp, 20)]
+  #[case(3, 7, EdgeMode::Clamp, 42)]
+  #[case(-1, -1, EdgeMode::Mirror, 11)]
+  #[case(-2, 0, EdgeMode::Mirror, 2)]
+  #[case(3, 5, EdgeMode::Mirror, 31)]
+  #[case(4, 6, EdgeMode::Mirror, 20)]
+  #[case(6, 9, EdgeMode::Mirror, 12)]
+  #[case(1, 2, EdgeMode::Mirror, 21)]
+  fn test_get_edge(
+    #[case] x: isize,
+    #[case] y: isize,
+    #[case] mode: EdgeMode,
+    #[case] expected: u16,
+  ) {
+    assert_eq!(*view(&DATA).get_edge(x, y, mode), expected);
+  }
+
+  #[test]
+  #[should_panic(expected = "empty 0x5 plane")]
+  fn test_get_edge_empty() {
+    let plane = PlaneView {
+      data: &DATA,
+      height: 5,
+      width: 0,
+      stride: 8,
+    };
+    let _ = plane.get_edge(0, 0, EdgeMode::Clamp);
+  }
+
+  #[rstest]
+  #[case(GRAY8, true, false, false, false)]
+  #[case(YUV420P10, false, true, false, false)]
+  #[case(GRAY16, false, true, false, false)]
+  #[case(GRAY32, false, false, true, false)]
+  #[case(GRAYH, false, false, false, false)]
+  #[case(GRAYS, false, false, false, true)]
+  #[case(YUV444PS, false, false, false, true)]
+  fn test_pixel_matches_format(
+    #[case] format: VideoFormat,
+    #[case] is_u8: bool,
+    #[case] is_u16: bool,
+    #[case] is_u32: bool,
+    #[case] is_f32: bool,
+  ) {
+    assert_eq!(u8::matches_format(&format), is_u8);
+    assert_eq!(u16::matches_format(&format), is_u16);
+    assert_eq!(u32::matches_format(&format), is_u32);
+    assert_eq!(f32::matches_format(&format), is_f32);
+  }
+
+  #[test]
+  fn test_pixel_check_format() {
+    assert!(u16::check_format(&GRAY16).is_ok());
+    assert!(matches!(
+      u8::check_format(&GRAY16),
+      Err(VapoursError::PixelTypeMismatchError(
+        "u8",
+        2,
+        SampleType::Integer
+      ))
+    ));
+    assert!(matches!(
+      f32::check_format(&GRAY32),
+      Err(VapoursError::PixelTypeMismatchError(
+        "f32",
+        4,
+        SampleType::Integer
+      ))
+    ));
+  }
+
+  #[cfg(feature = "half")]
+  #[test]
+  fn test_half_pixel_matches_format() {
+    assert!(half::f16::matches_format(&GRAYH));
+    assert!(!half::f16::matches_format(&GRAY16));
+    assert!(!half::f16::matches_format(&GRAYS));
+  }
+
+  struct TypeName;
+
+  impl PixelVisitor for TypeName {
+    type Output = &'static str;
+
+    fn visit<T: Pixel>(self) -> &'static str {
+      std::any::type_name::<T>()
+    }
+  }
+
+  #[rstest]
+  #[case(GRAY8, "u8")]
+  #[case(YUV420P10, "u16")]
+  #[case(GRAY16, "u16")]
+  #[case(GRAY32, "u32")]
+  #[case(GRAYS, "f32")]
+  #[case(YUV444PS, "f32")]
+  fn test_dispatch(#[case] format: VideoFormat, #[case] expected: &str) {
+    assert_eq!(dispatch(&format, TypeName).ok(), Some(expected));
+  }
+
+  #[test]
+  fn test_dispatch_half() {
+    let result = dispatch(&GRAYH, TypeName);
+    if cfg!(feature = "half") {
+      assert_eq!(result.ok(), Some("half::binary16::f16"));
+    } else {
+      assert!(matches!(
+        result,
+        Err(VapoursError::UnsupportedFormatError(_))
+      ));
+    }
+  }
+}
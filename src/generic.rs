@@ -7,7 +7,11 @@ use vapoursynth4_rs::{
   ColorFamily, SampleType, VideoInfo,
 };
 
-use crate::enums::ColorRange;
+use crate::{
+  enums::ColorRange,
+  errors::VapoursError,
+  vs_enums::{is_valid_video_format, make_video_format},
+};
 
 /// A trait for types that hold a video format.
 pub trait HoldsVideoFormat: Sized {
@@ -33,6 +37,49 @@ pub trait HoldsVideoFormat: Sized {
     self.video_format().sample_type
   }
 
+  /// Get the number of planes of this clip or format.
+  #[must_use]
+  fn num_planes(&self) -> i32 {
+    self.video_format().num_planes
+  }
+
+  /// Returns whether `plane` holds chroma, i.e. is the U or V plane of a YUV
+  /// clip or format.
+  #[must_use]
+  fn is_chroma_plane(&self, plane: i32) -> bool {
+    self.color_family() == ColorFamily::YUV && plane > 0
+  }
+
+  /// Returns the horizontal and vertical chroma subsampling ratios, e.g.
+  /// `(2, 2)` for 4:2:0.
+  #[must_use]
+  fn subsampling_ratio(&self) -> (i32, i32) {
+    let format = self.video_format();
+    (1 << format.sub_sampling_w, 1 << format.sub_sampling_h)
+  }
+
+  /// Returns the width of `plane` for a frame that is `frame_width` pixels
+  /// wide, matching [`VideoFrame::frame_width`].
+  #[must_use]
+  fn plane_width(&self, plane: i32, frame_width: i32) -> i32 {
+    if plane > 0 {
+      frame_width >> self.video_format().sub_sampling_w
+    } else {
+      frame_width
+    }
+  }
+
+  /// Returns the height of `plane` for a frame that is `frame_height` pixels
+  /// high, matching [`VideoFrame::frame_height`].
+  #[must_use]
+  fn plane_height(&self, plane: i32, frame_height: i32) -> i32 {
+    if plane > 0 {
+      frame_height >> self.video_format().sub_sampling_h
+    } else {
+      frame_height
+    }
+  }
+
   /// Returns the lowest value for the bit depth of this clip or format.
   #[must_use]
   fn lowest_value(&self, chroma: Option<bool>, range_in: Option<ColorRange>) -> f32 {
@@ -104,6 +151,133 @@ pub trait HoldsVideoFormat: Sized {
       .to_f32()
       .expect("result should fit in a f32")
   }
+
+  /// Returns this format with another bit depth. As in
+  /// [`VapoursCore::depth`](crate::utils::VapoursCore::depth), the sample
+  /// type becomes float for 32 bits and integer otherwise.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if VapourSynth does not accept the resulting format,
+  /// e.g. for fewer than 8 or more than 32 bits.
+  fn with_depth(&self, bits_per_sample: i32) -> Result<VideoFormat, VapoursError> {
+    let format = self.video_format();
+    let sample_type = if bits_per_sample == 32 {
+      SampleType::Float
+    } else {
+      SampleType::Integer
+    };
+    checked_video_format(make_video_format(
+      format.color_family,
+      sample_type,
+      bits_per_sample,
+      format.sub_sampling_w,
+      format.sub_sampling_h,
+    ))
+  }
+
+  /// Returns this format with another sample type. Integer formats become
+  /// single precision float formats, while float formats keep their bit
+  /// depth.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if VapourSynth does not accept the resulting format.
+  fn with_sample_type(&self, sample_type: SampleType) -> Result<VideoFormat, VapoursError> {
+    let format = self.video_format();
+    let bits_per_sample = match (format.sample_type, sample_type) {
+      (SampleType::Integer, SampleType::Float) => 32,
+      _ => format.bits_per_sample,
+    };
+    checked_video_format(make_video_format(
+      format.color_family,
+      sample_type,
+      bits_per_sample,
+      format.sub_sampling_w,
+      format.sub_sampling_h,
+    ))
+  }
+
+  /// Returns this format with another chroma subsampling, given as log2 of
+  /// the horizontal and vertical ratios.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if VapourSynth does not accept the resulting format,
+  /// i.e. if the format is not YUV and the subsampling is not `(0, 0)`, or if
+  /// a ratio is negative or more than 4.
+  fn with_subsampling(
+    &self,
+    sub_sampling_w: i32,
+    sub_sampling_h: i32,
+  ) -> Result<VideoFormat, VapoursError> {
+    let format = self.video_format();
+    checked_video_format(make_video_format(
+      format.color_family,
+      format.sample_type,
+      format.bits_per_sample,
+      sub_sampling_w,
+      sub_sampling_h,
+    ))
+  }
+
+  /// Returns this format with another color family. Subsampling is only kept
+  /// when converting to YUV.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if VapourSynth does not accept the resulting format,
+  /// e.g. for [`ColorFamily::Undefined`].
+  fn with_color_family(&self, color_family: ColorFamily) -> Result<VideoFormat, VapoursError> {
+    let format = self.video_format();
+    let (sub_sampling_w, sub_sampling_h) = if color_family == ColorFamily::YUV {
+      (format.sub_sampling_w, format.sub_sampling_h)
+    } else {
+      (0, 0)
+    };
+    checked_video_format(make_video_format(
+      color_family,
+      format.sample_type,
+      format.bits_per_sample,
+      sub_sampling_w,
+      sub_sampling_h,
+    ))
+  }
+
+  /// Returns the GRAY format with the same sample type and bit depth.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if this format is not accepted by VapourSynth.
+  fn to_gray(&self) -> Result<VideoFormat, VapoursError> {
+    self.with_color_family(ColorFamily::Gray)
+  }
+
+  /// Returns the float format of the same family and subsampling. Integer
+  /// formats become single precision and float formats are kept as they are.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if this format is not accepted by VapourSynth.
+  fn to_float(&self) -> Result<VideoFormat, VapoursError> {
+    self.with_sample_type(SampleType::Float)
+  }
+}
+
+/// Returns `format` if VapourSynth accepts it.
+fn checked_video_format(format: VideoFormat) -> Result<VideoFormat, VapoursError> {
+  if is_valid_video_format(&format) {
+    Ok(format)
+  } else {
+    Err(VapoursError::UnsupportedFormatError(format!(
+      "{:?} {:?} {} bits per sample with subsampling ({}, {})",
+      format.color_family,
+      format.sample_type,
+      format.bits_per_sample,
+      format.sub_sampling_w,
+      format.sub_sampling_h
+    )))
+  }
 }
 
 impl HoldsVideoFormat for VideoFrame {
@@ -137,8 +311,8 @@ mod tests {
   use vapoursynth4_rs::frame::VideoFormat;
 
   use crate::vs_enums::{
-    GRAY16, GRAY8, GRAYH, GRAYS, RGB24, RGBH, RGBS, YUV420P16, YUV420P8, YUV420PS, YUV444P16,
-    YUV444P8, YUV444PS,
+    GRAY16, GRAY8, GRAYH, GRAYS, RGB24, RGB48, RGBH, RGBS, YUV410P8, YUV420P10, YUV420P16,
+    YUV420P8, YUV420PH, YUV420PS, YUV422PS, YUV444P16, YUV444P8, YUV444PS,
   };
 
   use super::*;
@@ -203,4 +377,115 @@ mod tests {
   fn test_peak_value_defaults(#[case] format: VideoFormat, #[case] expected: f32) {
     assert_relative_eq!(format.peak_value(None, None), expected);
   }
+
+  #[rstest]
+  #[case(YUV420P8, 10, YUV420P10)]
+  #[case(YUV420P16, 32, YUV420PS)]
+  #[case(YUV420PS, 8, YUV420P8)]
+  #[case(RGBH, 16, RGB48)]
+  fn test_with_depth(
+    #[case] format: VideoFormat,
+    #[case] bits_per_sample: i32,
+    #[case] expected: VideoFormat,
+  ) {
+    assert_eq!(format.with_depth(bits_per_sample).unwrap(), expected);
+  }
+
+  #[rstest]
+  #[case(YUV420P8, SampleType::Float, YUV420PS)]
+  #[case(GRAY16, SampleType::Float, GRAYS)]
+  #[case(GRAYH, SampleType::Integer, GRAY16)]
+  #[case(YUV420PH, SampleType::Integer, YUV420P16)]
+  #[case(RGBH, SampleType::Float, RGBH)]
+  #[case(GRAY8, SampleType::Float, GRAYS)]
+  fn test_with_sample_type(
+    #[case] format: VideoFormat,
+    #[case] sample_type: SampleType,
+    #[case] expected: VideoFormat,
+  ) {
+    assert_eq!(format.with_sample_type(sample_type).unwrap(), expected);
+  }
+
+  #[test]
+  fn test_with_subsampling() {
+    assert_eq!(YUV444P16.with_subsampling(1, 1).unwrap(), YUV420P16);
+    assert_eq!(YUV420PS.with_subsampling(1, 0).unwrap(), YUV422PS);
+  }
+
+  #[rstest]
+  #[case(GRAY8.with_subsampling(1, 1))]
+  #[case(RGB24.with_subsampling(1, 1))]
+  #[case(YUV420P8.with_subsampling(5, 0))]
+  #[case(YUV420P8.with_subsampling(-1, 0))]
+  #[case(GRAY8.with_depth(7))]
+  #[case(GRAY8.with_depth(40))]
+  #[case(make_video_format(ColorFamily::Undefined, SampleType::Integer, 0, 0, 0).with_sample_type(SampleType::Float))]
+  #[case(YUV420P8.with_color_family(ColorFamily::Undefined))]
+  fn test_with_invalid(#[case] result: Result<VideoFormat, VapoursError>) {
+    assert!(matches!(
+      result,
+      Err(VapoursError::UnsupportedFormatError(_))
+    ));
+  }
+
+  #[rstest]
+  #[case(YUV420P16, ColorFamily::Gray, GRAY16)]
+  #[case(YUV420P8, ColorFamily::RGB, RGB24)]
+  #[case(RGBS, ColorFamily::YUV, YUV444PS)]
+  #[case(GRAYH, ColorFamily::RGB, RGBH)]
+  fn test_with_color_family(
+    #[case] format: VideoFormat,
+    #[case] color_family: ColorFamily,
+    #[case] expected: VideoFormat,
+  ) {
+    assert_eq!(format.with_color_family(color_family).unwrap(), expected);
+  }
+
+  #[test]
+  fn test_to_gray_and_float() {
+    assert_eq!(YUV420P8.to_gray().unwrap(), GRAY8);
+    assert_eq!(RGBS.to_gray().unwrap(), GRAYS);
+    assert_eq!(YUV420P8.to_float().unwrap(), YUV420PS);
+    assert_eq!(GRAYH.to_float().unwrap(), GRAYH);
+    assert_eq!(
+      YUV420P16
+        .to_gray()
+        .and_then(|format| format.to_float())
+        .unwrap()
+        .bytes_per_sample,
+      4
+    );
+  }
+
+  #[rstest]
+  #[case(YUV420P8, 1, (960, 540))]
+  #[case(YUV420P8, 0, (1920, 1080))]
+  #[case(YUV422PS, 2, (960, 1080))]
+  #[case(YUV410P8, 1, (480, 270))]
+  #[case(RGB24, 2, (1920, 1080))]
+  fn test_plane_dimensions(
+    #[case] format: VideoFormat,
+    #[case] plane: i32,
+    #[case] expected: (i32, i32),
+  ) {
+    assert_eq!(
+      (
+        format.plane_width(plane, 1920),
+        format.plane_height(plane, 1080)
+      ),
+      expected
+    );
+  }
+
+  #[test]
+  fn test_plane_layout() {
+    assert_eq!(YUV420P8.num_planes(), 3);
+    assert_eq!(GRAY16.num_planes(), 1);
+    assert!(!YUV420P8.is_chroma_plane(0));
+    assert!(YUV420P8.is_chroma_plane(2));
+    assert!(!RGB24.is_chroma_plane(1));
+    assert_eq!(YUV420P8.subsampling_ratio(), (2, 2));
+    assert_eq!(YUV422PS.subsampling_ratio(), (2, 1));
+    assert_eq!(RGBS.subsampling_ratio(), (1, 1));
+  }
 }
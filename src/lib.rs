@@ -5,7 +5,7 @@
 //!
 //! For example, the classic invert filter goes from this:
 //!
-//! ```rust
+//! ```ignore
 //! for plane in 0..fi.num_planes {
 //!     let mut src_p = src.plane(plane);
 //!     let src_stride = src.stride(plane);
@@ -28,29 +28,40 @@
 //!
 //! To this:
 //!
-//! ```rust
-//! // Bring in extensions on `VideoFrame` like `as_slice()` and `as_mut_slice()`.
-//! use vapours::frame::VapoursVideoFrame;
+//! ```
+//! # use std::iter::zip;
+//! #
+//! // Bring in extensions on `VideoFrame` like `planes_iter()` and
+//! // `planes_iter_mut()`.
+//! use vapours::frame::{zip_rows, VapoursVideoFrame};
 //!
 //! // ...
 //!
-//! for plane in 0..fi.num_planes {
-//!     let src_slice = src.as_slice::<u8>(plane);
-//!     let dst_slice = dst.as_mut_slice::<u8>(plane);
-//!
-//!     for (src_pixel, dst_pixel) in zip(src_slice, dst_slice) {
-//!         *dst_pixel = !*src_pixel;
+//! # fn get_frame(src: &vapoursynth4_rs::frame::VideoFrame, dst: &mut vapoursynth4_rs::frame::VideoFrame) {
+//! for (src_plane, mut dst_plane) in zip(src.planes_iter::<u8>(), dst.planes_iter_mut::<u8>()) {
+//!     for (src_row, dst_row) in zip_rows(&src_plane, &mut dst_plane) {
+//!         for (src_pixel, dst_pixel) in zip(src_row, dst_row) {
+//!             *dst_pixel = !*src_pixel;
+//!         }
 //!     }
 //! }
+//! # }
 //! ```
 
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+
 #[macro_use]
 extern crate num_derive;
 
+pub mod check;
+pub mod dither;
 pub mod enums;
 pub mod errors;
 pub mod frame;
 pub mod generic;
+pub mod lut;
+pub mod planes;
+pub mod props;
 pub mod scale;
 pub mod utils;
 pub mod vs_enums;
@@ -0,0 +1,372 @@
+//! Lookup tables.
+//!
+//! Per-pixel transforms of integer formats of up to 16 bits are usually
+//! cheaper to compute once per possible input value and look up afterwards.
+
+use std::iter::zip;
+
+use vapoursynth4_rs::SampleType;
+
+use crate::{
+  enums::ColorRange,
+  errors::VapoursError,
+  frame::{zip_rows, IntegerPixel, Pixel, PlaneView, PlaneViewMut},
+  generic::HoldsVideoFormat,
+  scale::ScaleParams,
+  vs_enums::make_video_format,
+};
+
+/// Largest bit depth of the input of a [`Lut`].
+const MAX_LUT_BITS: i32 = 16;
+
+/// Largest sum of the bit depths of the inputs of a [`Lut2`], as in
+/// `std.Lut2`.
+const MAX_LUT2_BITS: i32 = 20;
+
+/// A table mapping every value of an integer format to a pixel of type `D`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Lut<D> {
+  table: Vec<D>,
+}
+
+impl<D: Pixel> Lut<D> {
+  /// Builds the table by calling `f` on every value of `format_in`. Results
+  /// are rounded and clamped for an integer `format_out`.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if `format_in` is not an integer format of at most 16
+  /// bits or if `D` does not match `format_out`.
+  #[allow(clippy::cast_precision_loss)] // Values have at most 16 bits.
+  pub fn new<U, V, F>(format_in: &U, format_out: &V, f: F) -> Result<Self, VapoursError>
+  where
+    U: HoldsVideoFormat,
+    V: HoldsVideoFormat,
+    F: Fn(f32) -> f32,
+  {
+    check_lut_input(format_in, MAX_LUT_BITS)?;
+    D::check_format(format_out)?;
+
+    let quantize = Quantize::new(format_out);
+    let table = (0..1u32 << format_in.depth())
+      .map(|value| D::from_f32(quantize.apply(f(value as f32))))
+      .collect();
+    Ok(Self { table })
+  }
+
+  /// Builds the table by calling `f` on every value of `format_in` normalised
+  /// as by [`scale_value`](crate::scale::scale_value) to a float format,
+  /// i.e. to `[0, 1]` or to `[-0.5, 0.5]` for chroma. Results are scaled back
+  /// from the float format to `format_out`. Ranges and `chroma` default as in
+  /// [`scale_value`](crate::scale::scale_value).
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if `format_in` is not an integer format of at most 16
+  /// bits or if `D` does not match `format_out`.
+  pub fn new_normalized<U, V, F>(
+    format_in: &U,
+    format_out: &V,
+    range_in: Option<ColorRange>,
+    range_out: Option<ColorRange>,
+    chroma: Option<bool>,
+    f: F,
+  ) -> Result<Self, VapoursError>
+  where
+    U: HoldsVideoFormat,
+    V: HoldsVideoFormat,
+    F: Fn(f32) -> f32,
+  {
+    let float_in = make_video_format(format_in.color_family(), SampleType::Float, 32, 0, 0);
+    let float_out = make_video_format(format_out.color_family(), SampleType::Float, 32, 0, 0);
+
+    let mut normalize = ScaleParams::builder(format_in, &float_in);
+    let mut denormalize = ScaleParams::builder(&float_out, format_out);
+    if let Some(range_in) = range_in {
+      normalize = normalize.range_in(range_in);
+    }
+    if let Some(range_out) = range_out {
+      denormalize = denormalize.range_out(range_out);
+    }
+    if let Some(chroma) = chroma {
+      normalize = normalize.chroma(chroma);
+      denormalize = denormalize.chroma(chroma);
+    }
+    let (normalize, denormalize) = (normalize.build(), denormalize.build());
+
+    Self::new(format_in, format_out, |value| {
+      denormalize.apply(f(normalize.apply(value)))
+    })
+  }
+
+  /// Number of entries, i.e. of values of the input format.
+  #[must_use]
+  pub const fn len(&self) -> usize {
+    self.table.len()
+  }
+
+  /// Whether the table has no entries, which never happens once built.
+  #[must_use]
+  pub const fn is_empty(&self) -> bool {
+    self.table.is_empty()
+  }
+
+  /// Returns the entry of an input value. Values beyond the input format's
+  /// peak use the last entry.
+  #[inline]
+  #[must_use]
+  pub fn get(&self, value: usize) -> D {
+    self.table[value.min(self.table.len() - 1)]
+  }
+
+  /// Maps every pixel of `src` into `dst`.
+  pub fn apply<S: IntegerPixel>(&self, src: &PlaneView<'_, S>, dst: &mut PlaneViewMut<'_, D>) {
+    for (src_row, dst_row) in zip_rows(src, dst) {
+      for (src_pixel, dst_pixel) in zip(src_row, dst_row) {
+        *dst_pixel = self.get(src_pixel.to_usize());
+      }
+    }
+  }
+}
+
+/// A table mapping every pair of values of two integer formats to a pixel of
+/// type `D`, like `std.Lut2`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Lut2<D> {
+  table: Vec<D>,
+  bits_x: i32,
+  bits_y: i32,
+}
+
+impl<D: Pixel> Lut2<D> {
+  /// Builds the table by calling `f` on every pair of values of `format_x`
+  /// and `format_y`. Results are rounded and clamped for an integer
+  /// `format_out`.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if an input format is not an integer format, if their
+  /// bit depths add up to more than 20 or if `D` does not match `format_out`.
+  #[allow(clippy::cast_precision_loss)] // Values have at most 20 bits.
+  pub fn new<X, Y, V, F>(
+    format_x: &X,
+    format_y: &Y,
+    format_out: &V,
+    f: F,
+  ) -> Result<Self, VapoursError>
+  where
+    X: HoldsVideoFormat,
+    Y: HoldsVideoFormat,
+    V: HoldsVideoFormat,
+    F: Fn(f32, f32) -> f32,
+  {
+    let (bits_x, bits_y) = (format_x.depth(), format_y.depth());
+    check_lut_input(format_x, MAX_LUT2_BITS)?;
+    check_lut_input(format_y, MAX_LUT2_BITS)?;
+    if bits_x + bits_y > MAX_LUT2_BITS {
+      return Err(VapoursError::UnsupportedFormatError(format!(
+        "lookup tables of two inputs need at most {MAX_LUT2_BITS} bits in total, got {bits_x} and \
+         {bits_y} bits"
+      )));
+    }
+    D::check_format(format_out)?;
+
+    let quantize = Quantize::new(format_out);
+    let table = (0..1u32 << bits_y)
+      .flat_map(|y| (0..1u32 << bits_x).map(move |x| (x, y)))
+      .map(|(x, y)| D::from_f32(quantize.apply(f(x as f32, y as f32))))
+      .collect();
+    Ok(Self {
+      table,
+      bits_x,
+      bits_y,
+    })
+  }
+
+  /// Returns the entry of a pair of input values. Values beyond the peak of
+  /// their format are clamped to it.
+  #[inline]
+  #[must_use]
+  pub fn get(&self, x: usize, y: usize) -> D {
+    let x = x.min((1 << self.bits_x) - 1);
+    let y = y.min((1 << self.bits_y) - 1);
+    self.table[(y << self.bits_x) | x]
+  }
+
+  /// Maps every pair of pixels of `src_x` and `src_y` into `dst`.
+  pub fn apply<X, Y>(
+    &self,
+    src_x: &PlaneView<'_, X>,
+    src_y: &PlaneView<'_, Y>,
+    dst: &mut PlaneViewMut<'_, D>,
+  ) where
+    X: IntegerPixel,
+    Y: IntegerPixel,
+  {
+    debug_assert_eq!(src_x.width, src_y.width);
+    debug_assert_eq!(src_x.height, src_y.height);
+    for ((x_row, dst_row), y_row) in zip(zip_rows(src_x, dst), src_y.rows()) {
+      for ((x, y), dst_pixel) in zip(zip(x_row, y_row), dst_row) {
+        *dst_pixel = self.get(x.to_usize(), y.to_usize());
+      }
+    }
+  }
+}
+
+/// Returns an error unless `format` is an integer format of at most
+/// `max_bits`.
+fn check_lut_input<F: HoldsVideoFormat>(format: &F, max_bits: i32) -> Result<(), VapoursError> {
+  if format.sample_type() == SampleType::Integer && format.depth() <= max_bits {
+    Ok(())
+  } else {
+    Err(VapoursError::UnsupportedFormatError(format!(
+      "lookup tables need integer input of at most {max_bits} bits, got {:?} {} bits",
+      format.sample_type(),
+      format.depth()
+    )))
+  }
+}
+
+/// Rounding and clamping of values to an output format.
+struct Quantize {
+  peak: Option<f32>,
+}
+
+impl Quantize {
+  fn new<V: HoldsVideoFormat>(format_out: &V) -> Self {
+    Self {
+      peak: (format_out.sample_type() == SampleType::Integer)
+        .then(|| format_out.peak_value(None, Some(ColorRange::Full))),
+    }
+  }
+
+  fn apply(&self, value: f32) -> f32 {
+    self
+      .peak
+      .map_or(value, |peak| value.round().clamp(0.0, peak))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use approx::assert_relative_eq;
+
+  use crate::vs_enums::{GRAY10, GRAY16, GRAY8, GRAYS, RGB24, YUV444P8};
+
+  use super::*;
+
+  #[test]
+  fn test_lut() {
+    let lut = Lut::<u8>::new(&GRAY8, &GRAY8, |x| 255.0 - x).unwrap();
+    assert_eq!(lut.len(), 256);
+    assert_eq!(lut.get(0), 255);
+    assert_eq!(lut.get(200), 55);
+
+    let lut = Lut::<u16>::new(&GRAY10, &GRAY16, |x| x * 100.0).unwrap();
+    assert_eq!(lut.len(), 1024);
+    assert_eq!(lut.get(10), 1000);
+    assert_eq!(lut.get(1000), 65535);
+    assert_eq!(lut.get(5000), 65535);
+  }
+
+  #[test]
+  fn test_lut_errors() {
+    assert!(matches!(
+      Lut::<u8>::new(&GRAYS, &GRAY8, |x| x),
+      Err(VapoursError::UnsupportedFormatError(_))
+    ));
+    assert!(matches!(
+      Lut::<u8>::new(&GRAY8, &GRAY16, |x| x),
+      Err(VapoursError::PixelTypeMismatchError(..))
+    ));
+  }
+
+  #[test]
+  fn test_lut_normalized() {
+    let lut = Lut::<f32>::new_normalized(&GRAY8, &GRAYS, None, None, None, |x| x * x).unwrap();
+    assert_relative_eq!(lut.get(16), 0.0);
+    assert_relative_eq!(lut.get(235), 1.0);
+
+    let lut = Lut::<u8>::new_normalized(&RGB24, &GRAY8, None, Some(ColorRange::Full), None, |x| {
+      1.0 - x
+    })
+    .unwrap();
+    assert_eq!(lut.get(0), 255);
+    assert_eq!(lut.get(51), 204);
+
+    let lut =
+      Lut::<u8>::new_normalized(&YUV444P8, &YUV444P8, None, None, Some(true), |x| -x).unwrap();
+    assert_eq!(lut.get(128), 128);
+    assert_eq!(lut.get(16), 240);
+  }
+
+  #[test]
+  fn test_lut_apply() {
+    let src_data = [0u16, 512, 1023, 0, 100, 200, 300, 0];
+    let src = PlaneView {
+      data: &src_data,
+      height: 2,
+      width: 3,
+      stride: 8,
+    };
+    let mut dst_data = [0u8; 6];
+    let mut dst = PlaneViewMut {
+      data: &mut dst_data,
+      height: 2,
+      width: 3,
+      stride: 3,
+    };
+
+    let lut = Lut::new(&GRAY10, &GRAY8, |x| x / 4.0).unwrap();
+    lut.apply(&src, &mut dst);
+    assert_eq!(dst_data, [0, 128, 255, 25, 50, 75]);
+  }
+
+  #[test]
+  fn test_lut2() {
+    let lut = Lut2::<u8>::new(&GRAY8, &GRAY8, &GRAY8, |x, y| x.max(y) - x.min(y)).unwrap();
+    assert_eq!(lut.get(10, 250), 240);
+    assert_eq!(lut.get(250, 10), 240);
+    assert_eq!(lut.get(300, 0), 255);
+
+    assert!(matches!(
+      Lut2::<u8>::new(&GRAY16, &GRAY8, &GRAY8, |x, _| x),
+      Err(VapoursError::UnsupportedFormatError(message))
+        if message.ends_with("at most 20 bits in total, got 16 and 8 bits")
+    ));
+    assert!(matches!(
+      Lut2::<u8>::new(&GRAY16, &GRAYS, &GRAY8, |x, _| x),
+      Err(VapoursError::UnsupportedFormatError(message))
+        if message.ends_with("got Float 32 bits")
+    ));
+  }
+
+  #[test]
+  fn test_lut2_apply() {
+    let x_data = [10u8, 20, 30, 40];
+    let y_data = [1u16, 2, 3, 4];
+    let src_x = PlaneView {
+      data: &x_data,
+      height: 2,
+      width: 2,
+      stride: 2,
+    };
+    let src_y = PlaneView {
+      data: &y_data,
+      height: 2,
+      width: 2,
+      stride: 4,
+    };
+    let mut dst_data = [0u16; 4];
+    let mut dst = PlaneViewMut {
+      data: &mut dst_data,
+      height: 2,
+      width: 2,
+      stride: 4,
+    };
+
+    let lut = Lut2::new(&GRAY8, &GRAY10, &GRAY16, |x, y| x * y).unwrap();
+    lut.apply(&src_x, &src_y, &mut dst);
+    assert_eq!(dst_data, [10, 40, 90, 160]);
+  }
+}
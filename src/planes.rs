@@ -0,0 +1,205 @@
+//! Plane selection.
+//!
+//! Filters commonly accept a `planes` argument that is omitted, a single
+//! plane, or a list of planes. [`Planes`] normalizes it against the format of
+//! a clip, like vs-tools' `normalize_planes`:
+//!
+//! - No argument selects every plane.
+//! - Negative indices count from the last plane, so `-1` is the last plane.
+//! - Planes that the format does not have are dropped.
+//! - A plane given more than once is an error.
+//!
+//! ```
+//! # use vapours::{errors::VapoursError, planes::Planes};
+//! # use vapoursynth4_rs::{key, map::Map, node::VideoNode};
+//! #
+//! # fn create(node: &VideoNode, args: &Map) -> Result<(), VapoursError> {
+//! let planes = Planes::from_map(node, args, key!(c"planes"))?;
+//! for plane in &planes {
+//!   // Process `plane`, and copy the others.
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{iter::Copied, slice};
+
+use vapoursynth4_rs::map::{KeyStr, Map, MapPropertyError};
+
+use crate::{
+  errors::{PropertyErrorReason, VapoursError},
+  generic::HoldsVideoFormat,
+};
+
+/// A normalized, sorted selection of planes.
+///
+/// Unlike an omitted `planes` argument, which selects every plane,
+/// [`Planes::default`] selects none. Use [`Planes::all`] for every plane.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Planes(Vec<i32>);
+
+impl Planes {
+  /// Selects every plane of `format`.
+  #[must_use]
+  pub fn all<T: HoldsVideoFormat>(format: &T) -> Self {
+    Self((0..format.num_planes()).collect())
+  }
+
+  /// Normalizes `planes` against `format`. [`None`] selects every plane.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if a plane is given more than once, including through
+  /// both its positive and negative index.
+  pub fn new<T: HoldsVideoFormat>(
+    format: &T,
+    planes: Option<&[i64]>,
+  ) -> Result<Self, VapoursError> {
+    let Some(planes) = planes else {
+      return Ok(Self::all(format));
+    };
+
+    let num_planes = i64::from(format.num_planes());
+    let mut normalized = Vec::with_capacity(planes.len());
+    for &plane in planes {
+      let index = if plane < 0 { plane + num_planes } else { plane };
+      if !(0..num_planes).contains(&index) {
+        continue;
+      }
+
+      let index = index as i32;
+      if normalized.contains(&index) {
+        return Err(VapoursError::InvalidPlanesError(format!(
+          "plane {plane} is given more than once"
+        )));
+      }
+      normalized.push(index);
+    }
+    normalized.sort_unstable();
+
+    Ok(Self(normalized))
+  }
+
+  /// Reads and normalizes the int or int array argument `key` of a filter.
+  /// A missing argument selects every plane.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the argument is not an int, or if a plane is given
+  /// more than once.
+  pub fn from_map<T: HoldsVideoFormat>(
+    format: &T,
+    args: &Map,
+    key: &KeyStr,
+  ) -> Result<Self, VapoursError> {
+    let Some(len) = args.num_elements(key) else {
+      return Self::new(format, None);
+    };
+
+    let planes = (0..len)
+      .map(|index| args.get_int(key, index))
+      .collect::<Result<Vec<_>, _>>()
+      .map_err(|error| {
+        VapoursError::InvalidPlanesError(match error {
+          MapPropertyError::InvalidType => format!("'{key}' must be an int or an array of ints"),
+          error => format!(
+            "could not read '{key}': {}",
+            PropertyErrorReason::from(error)
+          ),
+        })
+      })?;
+
+    Self::new(format, Some(&planes))
+  }
+
+  /// Whether `plane` is selected.
+  #[must_use]
+  pub fn contains(&self, plane: i32) -> bool {
+    self.0.contains(&plane)
+  }
+
+  /// Number of selected planes.
+  #[must_use]
+  pub const fn len(&self) -> usize {
+    self.0.len()
+  }
+
+  /// Whether no plane is selected.
+  #[must_use]
+  pub const fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  /// The selected planes, in ascending order.
+  #[must_use]
+  pub fn as_slice(&self) -> &[i32] {
+    &self.0
+  }
+
+  /// Iterates over the selected planes, in ascending order.
+  pub fn iter(&self) -> Copied<slice::Iter<'_, i32>> {
+    self.0.iter().copied()
+  }
+}
+
+impl<'a> IntoIterator for &'a Planes {
+  type Item = i32;
+  type IntoIter = Copied<slice::Iter<'a, i32>>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rstest::rstest;
+  use vapoursynth4_rs::frame::VideoFormat;
+
+  use crate::vs_enums::{GRAY8, RGBS, YUV420P8};
+
+  use super::*;
+
+  #[rstest]
+  #[case(YUV420P8, None, &[0, 1, 2])]
+  #[case(GRAY8, None, &[0])]
+  #[case(YUV420P8, Some(&[2, 0][..]), &[0, 2])]
+  #[case(RGBS, Some(&[-1][..]), &[2])]
+  #[case(YUV420P8, Some(&[-3, -2][..]), &[0, 1])]
+  #[case(GRAY8, Some(&[0, 1, 2][..]), &[0])]
+  #[case(YUV420P8, Some(&[3, -4][..]), &[])]
+  #[case(YUV420P8, Some(&[][..]), &[])]
+  fn test_planes_new(
+    #[case] format: VideoFormat,
+    #[case] planes: Option<&[i64]>,
+    #[case] expected: &[i32],
+  ) {
+    assert_eq!(Planes::new(&format, planes).unwrap().as_slice(), expected);
+  }
+
+  #[rstest]
+  #[case(&[1, 1])]
+  #[case(&[2, -1])]
+  #[case(&[0, 1, -3])]
+  fn test_planes_new_duplicates(#[case] planes: &[i64]) {
+    assert!(matches!(
+      Planes::new(&YUV420P8, Some(planes)),
+      Err(VapoursError::InvalidPlanesError(_))
+    ));
+  }
+
+  #[test]
+  fn test_planes_access() {
+    let planes = Planes::new(&YUV420P8, Some(&[-1, 0])).unwrap();
+    assert!(planes.contains(0));
+    assert!(!planes.contains(1));
+    assert!(planes.contains(2));
+    assert_eq!(planes.len(), 2);
+    assert!(!planes.is_empty());
+    assert_eq!(planes.iter().collect::<Vec<_>>(), [0, 2]);
+    assert_eq!((&planes).into_iter().collect::<Vec<i32>>(), [0, 2]);
+    assert!(Planes::default().is_empty());
+    assert_eq!(planes, Planes::new(&YUV420P8, Some(&[0, 2])).unwrap());
+    assert_eq!(Planes::all(&RGBS).len(), 3);
+  }
+}
@@ -0,0 +1,665 @@
+//! Typed frame property access.
+//!
+//! [`VapoursFrameProps`] reads and writes frame properties as Rust types,
+//! reporting failures as [`VapoursError::FramePropertyError`] with the name of
+//! the property and the reason it could not be accessed. The supported types
+//! are:
+//!
+//! | Property type | Read as                         | Written from                           |
+//! | ------------- | ------------------------------- | -------------------------------------- |
+//! | Int           | [`i64`], [`i32`], [`bool`]      | [`i64`], [`i32`], [`bool`]             |
+//! | Float         | [`f64`], [`f32`]                | [`f64`], [`f32`]                       |
+//! | UTF-8 data    | [`String`]                      | [`String`], [`&str`](str)              |
+//! | Binary data   | [`Vec<u8>`]                     | [`Vec<u8>`], [`&[u8]`](slice)          |
+//!
+//! Reading an [`i32`] or an [`f32`] fails with
+//! [`PropertyErrorReason::InvalidValue`] if the stored value does not fit,
+//! rather than saturating it.
+//!
+//! Arrays of any of these are accessed with
+//! [`get_prop_array`](VapoursFrameProps::get_prop_array) and
+//! [`set_prop_array`](VapoursFrameProps::set_prop_array).
+//!
+//! [`FrameProps`] reads and writes all of VapourSynth's reserved frame
+//! properties at once.
+
+use num_traits::{FromPrimitive, ToPrimitive};
+use vapoursynth4_rs::{
+  frame::{Frame, VideoFrame},
+  key,
+  map::{AppendMode, KeyStr, Map, Value},
+};
+
+use crate::{
+  enums::{ChromaLocation, ColorRange, FieldBased, Matrix, Primaries, Transfer},
+  errors::{PropertyErrorReason, VapoursError},
+};
+
+/// A type that can be read from a frame property.
+pub trait FromProp: Sized {
+  /// Reads the element at `index` of the property `key`.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the property is missing, holds a different type, has
+  /// no element at `index`, or holds a value that does not fit in `Self`.
+  fn from_prop(map: &Map, key: &KeyStr, index: i32) -> Result<Self, PropertyErrorReason>;
+}
+
+/// A type that can be written to a frame property.
+pub trait ToProp {
+  /// Converts this value to a property value.
+  fn to_value(&self) -> Value<'_>;
+}
+
+impl FromProp for i64 {
+  fn from_prop(map: &Map, key: &KeyStr, index: i32) -> Result<Self, PropertyErrorReason> {
+    Ok(map.get_int(key, index)?)
+  }
+}
+
+impl FromProp for i32 {
+  fn from_prop(map: &Map, key: &KeyStr, index: i32) -> Result<Self, PropertyErrorReason> {
+    int_to_i32(map.get_int(key, index)?)
+  }
+}
+
+impl FromProp for bool {
+  fn from_prop(map: &Map, key: &KeyStr, index: i32) -> Result<Self, PropertyErrorReason> {
+    Ok(map.get_int(key, index)? != 0)
+  }
+}
+
+impl FromProp for f64 {
+  fn from_prop(map: &Map, key: &KeyStr, index: i32) -> Result<Self, PropertyErrorReason> {
+    Ok(map.get_float(key, index)?)
+  }
+}
+
+impl FromProp for f32 {
+  fn from_prop(map: &Map, key: &KeyStr, index: i32) -> Result<Self, PropertyErrorReason> {
+    float_to_f32(map.get_float(key, index)?)
+  }
+}
+
+impl FromProp for String {
+  fn from_prop(map: &Map, key: &KeyStr, index: i32) -> Result<Self, PropertyErrorReason> {
+    Ok(map.get_utf8(key, index)?.to_owned())
+  }
+}
+
+impl FromProp for Vec<u8> {
+  fn from_prop(map: &Map, key: &KeyStr, index: i32) -> Result<Self, PropertyErrorReason> {
+    Ok(map.get_binary(key, index)?.to_vec())
+  }
+}
+
+impl ToProp for i64 {
+  fn to_value(&self) -> Value<'_> {
+    Value::Int(*self)
+  }
+}
+
+impl ToProp for i32 {
+  fn to_value(&self) -> Value<'_> {
+    Value::Int(i64::from(*self))
+  }
+}
+
+impl ToProp for bool {
+  fn to_value(&self) -> Value<'_> {
+    Value::Int(i64::from(*self))
+  }
+}
+
+impl ToProp for f64 {
+  fn to_value(&self) -> Value<'_> {
+    Value::Float(*self)
+  }
+}
+
+impl ToProp for f32 {
+  fn to_value(&self) -> Value<'_> {
+    Value::Float(f64::from(*self))
+  }
+}
+
+impl ToProp for str {
+  fn to_value(&self) -> Value<'_> {
+    Value::Utf8(self)
+  }
+}
+
+impl ToProp for String {
+  fn to_value(&self) -> Value<'_> {
+    self.as_str().to_value()
+  }
+}
+
+impl ToProp for [u8] {
+  fn to_value(&self) -> Value<'_> {
+    Value::Data(self)
+  }
+}
+
+impl ToProp for Vec<u8> {
+  fn to_value(&self) -> Value<'_> {
+    self.as_slice().to_value()
+  }
+}
+
+impl<T: ToProp + ?Sized> ToProp for &T {
+  fn to_value(&self) -> Value<'_> {
+    (**self).to_value()
+  }
+}
+
+/// Converts an integer property to an [`i32`], rejecting values that do not
+/// fit rather than saturating them.
+fn int_to_i32(value: i64) -> Result<i32, PropertyErrorReason> {
+  i32::try_from(value).map_err(|_| PropertyErrorReason::InvalidValue)
+}
+
+/// Converts a float property to an [`f32`], rejecting finite values that do
+/// not fit rather than saturating them.
+fn float_to_f32(value: f64) -> Result<f32, PropertyErrorReason> {
+  if value.is_finite() && value.abs() > f64::from(f32::MAX) {
+    return Err(PropertyErrorReason::InvalidValue);
+  }
+
+  Ok(value as f32)
+}
+
+/// Checks that a value can be written without the property map panicking,
+/// i.e. that data is at most [`i32::MAX`] bytes long.
+fn check_value(value: &Value<'_>) -> Result<(), PropertyErrorReason> {
+  let len = match value {
+    Value::Data(data) => data.len(),
+    Value::Utf8(data) => data.len(),
+    _ => 0,
+  };
+  if i32::try_from(len).is_err() {
+    return Err(PropertyErrorReason::InvalidValue);
+  }
+
+  Ok(())
+}
+
+/// [`VideoFrame`] extensions for typed frame property access.
+pub trait VapoursFrameProps {
+  /// Whether the frame has the property `key`.
+  #[must_use]
+  fn has_prop(&self, key: &KeyStr) -> bool;
+
+  /// Reads the property `key`. For array properties this is the first
+  /// element.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the property is missing, holds a different type, is
+  /// empty, or holds a value that does not fit in `T`.
+  fn get_prop<T: FromProp>(&self, key: &KeyStr) -> Result<T, VapoursError>;
+
+  /// Reads the element at `index` of the property `key`.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the property is missing, holds a different type, has
+  /// no element at `index`, or holds a value that does not fit in `T`.
+  fn get_prop_at<T: FromProp>(&self, key: &KeyStr, index: i32) -> Result<T, VapoursError>;
+
+  /// Reads every element of the property `key`.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the property is missing, holds a different type, or
+  /// holds a value that does not fit in `T`.
+  fn get_prop_array<T: FromProp>(&self, key: &KeyStr) -> Result<Vec<T>, VapoursError>;
+
+  /// Sets the property `key`, replacing any existing value.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the value is too large to be stored or if the
+  /// property map cannot be written to.
+  fn set_prop<T: ToProp + ?Sized>(&mut self, key: &KeyStr, value: &T) -> Result<(), VapoursError>;
+
+  /// Sets the property `key` to an array, replacing any existing value. An
+  /// empty slice removes the property.
+  ///
+  /// Every value is checked before the existing property is removed, and the
+  /// property is removed rather than left partially written if writing fails.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if a value is too large to be stored or if the property
+  /// map cannot be written to.
+  fn set_prop_array<T: ToProp>(&mut self, key: &KeyStr, values: &[T]) -> Result<(), VapoursError>;
+
+  /// Removes the property `key`, if it exists.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the property map cannot be written to.
+  fn delete_prop(&mut self, key: &KeyStr) -> Result<(), VapoursError>;
+}
+
+impl VapoursFrameProps for VideoFrame {
+  fn has_prop(&self, key: &KeyStr) -> bool {
+    self
+      .properties()
+      .and_then(|props| props.num_elements(key))
+      .is_some()
+  }
+
+  fn get_prop<T: FromProp>(&self, key: &KeyStr) -> Result<T, VapoursError> {
+    self.get_prop_at(key, 0)
+  }
+
+  fn get_prop_at<T: FromProp>(&self, key: &KeyStr, index: i32) -> Result<T, VapoursError> {
+    let props = self.properties().ok_or_else(|| unavailable(key))?;
+    T::from_prop(&props, key, index).map_err(|e| prop_error(key, e))
+  }
+
+  fn get_prop_array<T: FromProp>(&self, key: &KeyStr) -> Result<Vec<T>, VapoursError> {
+    let props = self.properties().ok_or_else(|| unavailable(key))?;
+    let len = props
+      .num_elements(key)
+      .ok_or_else(|| prop_error(key, PropertyErrorReason::Missing))?;
+
+    (0..len)
+      .map(|index| T::from_prop(&props, key, index).map_err(|e| prop_error(key, e)))
+      .collect()
+  }
+
+  fn set_prop<T: ToProp + ?Sized>(&mut self, key: &KeyStr, value: &T) -> Result<(), VapoursError> {
+    let value = value.to_value();
+    check_value(&value).map_err(|e| prop_error(key, e))?;
+
+    let mut props = self.properties_mut().ok_or_else(|| unavailable(key))?;
+    props
+      .set(key, value, AppendMode::Replace)
+      .map_err(|e| prop_error(key, e))
+  }
+
+  fn set_prop_array<T: ToProp>(&mut self, key: &KeyStr, values: &[T]) -> Result<(), VapoursError> {
+    let values: Vec<_> = values.iter().map(ToProp::to_value).collect();
+    values
+      .iter()
+      .try_for_each(check_value)
+      .map_err(|e| prop_error(key, e))?;
+
+    let mut props = self.properties_mut().ok_or_else(|| unavailable(key))?;
+    props.delete_key(key);
+    for value in values {
+      if let Err(e) = props.set(key, value, AppendMode::Append) {
+        props.delete_key(key);
+        return Err(prop_error(key, e));
+      }
+    }
+    Ok(())
+  }
+
+  fn delete_prop(&mut self, key: &KeyStr) -> Result<(), VapoursError> {
+    self
+      .properties_mut()
+      .ok_or_else(|| unavailable(key))?
+      .delete_key(key);
+    Ok(())
+  }
+}
+
+/// The value of an enum frame property such as `_Matrix`.
+///
+/// Values that `T` does not model, e.g. H.273 codes without a variant or
+/// newer than this crate, are kept as [`EnumProp::Unknown`] so that they are
+/// written back unchanged.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EnumProp<T> {
+  /// A value of `T`.
+  Known(T),
+
+  /// A value that `T` does not model.
+  Unknown(i64),
+}
+
+impl<T: FromPrimitive> EnumProp<T> {
+  /// Converts the integer stored in the property.
+  #[must_use]
+  pub fn from_raw(value: i64) -> Self {
+    T::from_i64(value).map_or(Self::Unknown(value), Self::Known)
+  }
+}
+
+impl<T: ToPrimitive> EnumProp<T> {
+  /// Returns the integer to store in the property.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `T` has no integer value, which never happens for the enums of
+  /// this crate.
+  #[must_use]
+  pub fn to_raw(&self) -> i64 {
+    match self {
+      Self::Known(value) => value.to_i64().expect("enum values should fit in an i64"),
+      Self::Unknown(value) => *value,
+    }
+  }
+}
+
+impl<T> EnumProp<T> {
+  /// Returns the value of `T`, or [`None`] if it is unknown.
+  #[must_use]
+  pub fn known(self) -> Option<T> {
+    match self {
+      Self::Known(value) => Some(value),
+      Self::Unknown(_) => None,
+    }
+  }
+}
+
+impl<T> From<T> for EnumProp<T> {
+  fn from(value: T) -> Self {
+    Self::Known(value)
+  }
+}
+
+/// VapourSynth's reserved frame properties.
+///
+/// Every field is [`None`] when the property is absent. A typical filter reads
+/// the properties of its source frame, modifies what it changes, and writes
+/// them to its output frame:
+///
+/// ```
+/// # use vapours::{enums::FieldBased, errors::VapoursError, props::FrameProps};
+/// # use vapoursynth4_rs::frame::VideoFrame;
+/// #
+/// # fn get_frame(src: &VideoFrame, dst: &mut VideoFrame) -> Result<(), VapoursError> {
+/// let mut props = FrameProps::from_frame(src)?;
+/// props.field_based = Some(FieldBased::Progressive.into());
+/// props.write_to_frame(dst)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrameProps {
+  /// `_Matrix`.
+  pub matrix: Option<EnumProp<Matrix>>,
+
+  /// `_Transfer`.
+  pub transfer: Option<EnumProp<Transfer>>,
+
+  /// `_Primaries`.
+  pub primaries: Option<EnumProp<Primaries>>,
+
+  /// `_ColorRange`.
+  pub color_range: Option<EnumProp<ColorRange>>,
+
+  /// `_ChromaLocation`.
+  pub chroma_location: Option<EnumProp<ChromaLocation>>,
+
+  /// `_FieldBased`.
+  pub field_based: Option<EnumProp<FieldBased>>,
+
+  /// Sample aspect ratio as `(_SARNum, _SARDen)`.
+  pub sar: Option<(i64, i64)>,
+
+  /// Frame duration in seconds as `(_DurationNum, _DurationDen)`.
+  pub duration: Option<(i64, i64)>,
+
+  /// `_AbsoluteTime`, the time in seconds at which the frame should be shown.
+  pub absolute_time: Option<f64>,
+
+  /// `_PictType`, usually a single character such as `I`, `P` or `B`.
+  pub pict_type: Option<String>,
+
+  /// `_Combed`.
+  pub combed: Option<bool>,
+
+  /// `_SceneChangePrev`.
+  pub scene_change_prev: Option<bool>,
+
+  /// `_SceneChangeNext`.
+  pub scene_change_next: Option<bool>,
+}
+
+impl FrameProps {
+  /// Reads the reserved properties of a frame.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if a property exists but holds a different type.
+  /// Missing properties are not an error, and neither is a `_PictType` that
+  /// is not data.
+  pub fn from_frame(frame: &VideoFrame) -> Result<Self, VapoursError> {
+    Ok(Self {
+      matrix: get_enum(frame, key!(c"_Matrix"))?,
+      transfer: get_enum(frame, key!(c"_Transfer"))?,
+      primaries: get_enum(frame, key!(c"_Primaries"))?,
+      color_range: get_enum(frame, key!(c"_ColorRange"))?,
+      chroma_location: get_enum(frame, key!(c"_ChromaLocation"))?,
+      field_based: get_enum(frame, key!(c"_FieldBased"))?,
+      sar: get_rational(frame, key!(c"_SARNum"), key!(c"_SARDen"))?,
+      duration: get_rational(frame, key!(c"_DurationNum"), key!(c"_DurationDen"))?,
+      absolute_time: optional(frame.get_prop(key!(c"_AbsoluteTime")))?,
+      pict_type: get_pict_type(frame)?,
+      combed: optional(frame.get_prop(key!(c"_Combed")))?,
+      scene_change_prev: optional(frame.get_prop(key!(c"_SceneChangePrev")))?,
+      scene_change_next: optional(frame.get_prop(key!(c"_SceneChangeNext")))?,
+    })
+  }
+
+  /// Writes the properties to a frame. Properties that are [`None`] are
+  /// removed from the frame.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the frame's properties cannot be written to.
+  pub fn write_to_frame(&self, frame: &mut VideoFrame) -> Result<(), VapoursError> {
+    write_or_delete(frame, key!(c"_Matrix"), self.matrix.map(|v| v.to_raw()))?;
+    write_or_delete(frame, key!(c"_Transfer"), self.transfer.map(|v| v.to_raw()))?;
+    write_or_delete(
+      frame,
+      key!(c"_Primaries"),
+      self.primaries.map(|v| v.to_raw()),
+    )?;
+    write_or_delete(
+      frame,
+      key!(c"_ColorRange"),
+      self.color_range.map(|v| v.to_raw()),
+    )?;
+    write_or_delete(
+      frame,
+      key!(c"_ChromaLocation"),
+      self.chroma_location.map(|v| v.to_raw()),
+    )?;
+    write_or_delete(
+      frame,
+      key!(c"_FieldBased"),
+      self.field_based.map(|v| v.to_raw()),
+    )?;
+    write_or_delete(frame, key!(c"_SARNum"), self.sar.map(|(num, _)| num))?;
+    write_or_delete(frame, key!(c"_SARDen"), self.sar.map(|(_, den)| den))?;
+    write_or_delete(
+      frame,
+      key!(c"_DurationNum"),
+      self.duration.map(|(num, _)| num),
+    )?;
+    write_or_delete(
+      frame,
+      key!(c"_DurationDen"),
+      self.duration.map(|(_, den)| den),
+    )?;
+    write_or_delete(frame, key!(c"_AbsoluteTime"), self.absolute_time)?;
+    write_or_delete(frame, key!(c"_PictType"), self.pict_type.as_deref())?;
+    write_or_delete(frame, key!(c"_Combed"), self.combed)?;
+    write_or_delete(frame, key!(c"_SceneChangePrev"), self.scene_change_prev)?;
+    write_or_delete(frame, key!(c"_SceneChangeNext"), self.scene_change_next)
+  }
+}
+
+/// Turns a missing property into [`None`], keeping every other error.
+fn optional<T>(result: Result<T, VapoursError>) -> Result<Option<T>, VapoursError> {
+  match result {
+    Ok(value) => Ok(Some(value)),
+    Err(VapoursError::FramePropertyError(_, PropertyErrorReason::Missing)) => Ok(None),
+    Err(e) => Err(e),
+  }
+}
+
+/// Reads an enum property, keeping values that `T` does not model.
+fn get_enum<T: FromPrimitive>(
+  frame: &VideoFrame,
+  key: &KeyStr,
+) -> Result<Option<EnumProp<T>>, VapoursError> {
+  Ok(optional(frame.get_prop(key))?.map(EnumProp::from_raw))
+}
+
+/// Reads `_PictType`, which older plugins store as binary data or without a
+/// data type hint rather than as a UTF-8 string. Anything but data reads as
+/// [`None`].
+fn get_pict_type(frame: &VideoFrame) -> Result<Option<String>, VapoursError> {
+  let key = key!(c"_PictType");
+  match optional(frame.get_prop::<String>(key)) {
+    Err(VapoursError::FramePropertyError(_, PropertyErrorReason::WrongType)) => {
+      match optional(frame.get_prop::<Vec<u8>>(key)) {
+        Ok(data) => Ok(data.map(|data| String::from_utf8_lossy(&data).into_owned())),
+        Err(VapoursError::FramePropertyError(_, PropertyErrorReason::WrongType)) => Ok(None),
+        Err(e) => Err(e),
+      }
+    }
+    result => result,
+  }
+}
+
+/// Reads a rational stored as two integer properties. Both must be present
+/// for the rational to be read.
+fn get_rational(
+  frame: &VideoFrame,
+  num_key: &KeyStr,
+  den_key: &KeyStr,
+) -> Result<Option<(i64, i64)>, VapoursError> {
+  let num = optional(frame.get_prop(num_key))?;
+  let den = optional(frame.get_prop(den_key))?;
+  Ok(num.zip(den))
+}
+
+/// Sets the property `key` if `value` is [`Some`], removes it otherwise.
+fn write_or_delete<T: ToProp>(
+  frame: &mut VideoFrame,
+  key: &KeyStr,
+  value: Option<T>,
+) -> Result<(), VapoursError> {
+  match value {
+    Some(value) => frame.set_prop(key, &value),
+    None => frame.delete_prop(key),
+  }
+}
+
+fn prop_error(key: &KeyStr, reason: impl Into<PropertyErrorReason>) -> VapoursError {
+  VapoursError::FramePropertyError(key.to_string(), reason.into())
+}
+
+fn unavailable(key: &KeyStr) -> VapoursError {
+  VapoursError::FramePropertyError(key.to_string(), PropertyErrorReason::Unavailable)
+}
+
+#[cfg(test)]
+mod tests {
+  use vapoursynth4_rs::map::MapPropertyError;
+
+  use super::*;
+
+  fn error(reason: PropertyErrorReason) -> VapoursError {
+    VapoursError::FramePropertyError("_Matrix".to_string(), reason)
+  }
+
+  #[test]
+  fn test_optional() {
+    assert_eq!(optional(Ok(1)).ok(), Some(Some(1)));
+    assert_eq!(
+      optional::<i64>(Err(error(PropertyErrorReason::Missing))).ok(),
+      Some(None)
+    );
+    assert!(matches!(
+      optional::<i64>(Err(error(PropertyErrorReason::WrongType))),
+      Err(VapoursError::FramePropertyError(
+        _,
+        PropertyErrorReason::WrongType
+      ))
+    ));
+  }
+
+  #[test]
+  fn test_enum_prop() {
+    assert_eq!(
+      EnumProp::<Matrix>::from_raw(1),
+      EnumProp::Known(Matrix::Bt709)
+    );
+    assert_eq!(EnumProp::from(Matrix::Bt709).known(), Some(Matrix::Bt709));
+    assert_eq!(EnumProp::from(Transfer::St2084).to_raw(), 16);
+    assert_eq!(EnumProp::<Matrix>::from_raw(3).known(), None);
+  }
+
+  #[test]
+  fn test_enum_prop_round_trip() {
+    // SMPTE ST 2085, the reserved 3 and future codes have no variant.
+    for value in [3, 11, 255] {
+      let matrix = EnumProp::<Matrix>::from_raw(value);
+      assert_eq!(matrix, EnumProp::Unknown(value));
+      assert_eq!(matrix.to_raw(), value);
+    }
+    assert_eq!(EnumProp::<Transfer>::from_raw(17).to_raw(), 17);
+    for value in [0, 1, 2, 3] {
+      assert_eq!(EnumProp::<FieldBased>::from_raw(value).to_raw(), value);
+    }
+  }
+
+  #[test]
+  fn test_int_to_i32() {
+    assert_eq!(int_to_i32(-12), Ok(-12));
+    assert_eq!(int_to_i32(i64::from(i32::MAX)), Ok(i32::MAX));
+    assert_eq!(
+      int_to_i32(i64::from(i32::MAX) + 1),
+      Err(PropertyErrorReason::InvalidValue)
+    );
+    assert_eq!(int_to_i32(i64::MIN), Err(PropertyErrorReason::InvalidValue));
+  }
+
+  #[test]
+  fn test_float_to_f32() {
+    assert_eq!(float_to_f32(0.25), Ok(0.25));
+    assert_eq!(float_to_f32(f64::INFINITY), Ok(f32::INFINITY));
+    assert!(float_to_f32(f64::NAN).unwrap().is_nan());
+    assert_eq!(float_to_f32(1e39), Err(PropertyErrorReason::InvalidValue));
+    assert_eq!(float_to_f32(-1e39), Err(PropertyErrorReason::InvalidValue));
+  }
+
+  #[test]
+  fn test_to_value() {
+    assert!(matches!(7i32.to_value(), Value::Int(7)));
+    assert!(matches!(true.to_value(), Value::Int(1)));
+    assert!(
+      matches!(0.5f32.to_value(), Value::Float(value) if value.to_bits() == 0.5f64.to_bits())
+    );
+    assert!(matches!("I".to_value(), Value::Utf8("I")));
+    assert!(matches!(vec![1u8, 2].to_value(), Value::Data([1, 2])));
+    assert!(check_value(&"I".to_value()).is_ok());
+  }
+
+  #[test]
+  fn test_prop_error() {
+    assert!(matches!(
+      prop_error(key!(c"_Matrix"), MapPropertyError::InvalidType),
+      VapoursError::FramePropertyError(key, PropertyErrorReason::WrongType) if key == "_Matrix"
+    ));
+    assert!(matches!(
+      prop_error(key!(c"_Matrix"), PropertyErrorReason::InvalidValue),
+      VapoursError::FramePropertyError(_, PropertyErrorReason::InvalidValue)
+    ));
+    assert!(matches!(
+      unavailable(key!(c"_Matrix")),
+      VapoursError::FramePropertyError(_, PropertyErrorReason::Unavailable)
+    ));
+  }
+}
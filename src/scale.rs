@@ -1,9 +1,15 @@
 //! Value scaling.
 
+use std::iter::zip;
+
 use num_traits::ToPrimitive;
 use vapoursynth4_rs::{ColorFamily, SampleType};
 
-use crate::{enums::ColorRange, generic::HoldsVideoFormat};
+use crate::{
+  enums::ColorRange,
+  frame::{zip_rows, Pixel, PlaneView, PlaneViewMut},
+  generic::HoldsVideoFormat,
+};
 
 /// Scale a value from one bit depth to another.
 ///
@@ -11,7 +17,6 @@ use crate::{enums::ColorRange, generic::HoldsVideoFormat};
 ///
 /// Will panic if the input value cannot fit in a [`f32`].
 #[must_use]
-#[allow(clippy::cast_precision_loss)]
 pub fn scale_value<T, U, V>(
   value: T,
   format_in: &U,
@@ -26,66 +31,368 @@ where
   U: HoldsVideoFormat,
   V: HoldsVideoFormat,
 {
-  let scale_offsets = scale_offsets.unwrap_or(true);
-
-  let is_input_rgb = format_in.color_family() == ColorFamily::RGB;
-  let is_output_rgb = format_out.color_family() == ColorFamily::RGB;
-
-  let range_in = range_in.unwrap_or(if is_input_rgb {
-    ColorRange::Full
-  } else {
-    ColorRange::Limited
-  });
-  let range_out = range_out.unwrap_or(if is_output_rgb {
-    ColorRange::Full
-  } else {
-    ColorRange::Limited
-  });
-
-  let mut out_value = value.to_f32().expect("value should fit in a f32");
-  if format_in.depth() == format_out.depth()
-    && range_in == range_out
-    && format_in.sample_type() == format_out.sample_type()
+  let value = value.to_f32().expect("value should fit in a f32");
+  let mut params = ScaleParams::builder(format_in, format_out);
+  if let Some(range_in) = range_in {
+    params = params.range_in(range_in);
+  }
+  if let Some(range_out) = range_out {
+    params = params.range_out(range_out);
+  }
+  if let Some(scale_offsets) = scale_offsets {
+    params = params.scale_offsets(scale_offsets);
+  }
+  if let Some(chroma) = chroma {
+    params = params.chroma(chroma);
+  }
+  params.build().apply(value)
+}
+
+/// Scale every pixel of a plane from one bit depth to another, with the same
+/// result as calling [`ScaleParams::apply`] on each of them.
+///
+/// With the `simd` feature, which requires a nightly compiler, rows are
+/// processed with portable SIMD.
+pub fn scale_plane<S, D>(
+  src: &PlaneView<'_, S>,
+  dst: &mut PlaneViewMut<'_, D>,
+  params: &ScaleParams,
+) where
+  S: Pixel,
+  D: Pixel,
+{
+  let scaling = params.scaling.unwrap_or(Scaling::IDENTITY);
+  let peak = params.integer_peak_out();
+
+  for (src_row, dst_row) in zip_rows(src, dst) {
+    scale_row(src_row, dst_row, scaling, peak);
+  }
+}
+
+/// Scales a row, rounding and clamping to `peak` for integer output.
+#[cfg(feature = "simd")]
+fn scale_row<S: Pixel, D: Pixel>(src: &[S], dst: &mut [D], scaling: Scaling, peak: Option<f32>) {
+  use std::simd::{f32x8, num::SimdFloat, StdFloat};
+
+  let (src_chunks, src_rest) = src.as_chunks::<8>();
+  let (dst_chunks, dst_rest) = dst.as_chunks_mut::<8>();
+
+  let offset_in = f32x8::splat(scaling.offset_in);
+  let gain = f32x8::splat(scaling.gain);
+  let offset_out = f32x8::splat(scaling.offset_out);
+  let zero = f32x8::splat(0.0);
+  for (src_chunk, dst_chunk) in zip(src_chunks, dst_chunks) {
+    let mut values = (f32x8::from_array(src_chunk.map(S::to_f32)) - offset_in) * gain + offset_out;
+    if let Some(peak) = peak {
+      values = values.round().simd_clamp(zero, f32x8::splat(peak));
+    }
+    *dst_chunk = values.to_array().map(D::from_f32);
+  }
+
+  scale_row_scalar(src_rest, dst_rest, scaling, peak);
+}
+
+/// Scales a row, rounding and clamping to `peak` for integer output.
+#[cfg(not(feature = "simd"))]
+fn scale_row<S: Pixel, D: Pixel>(src: &[S], dst: &mut [D], scaling: Scaling, peak: Option<f32>) {
+  scale_row_scalar(src, dst, scaling, peak);
+}
+
+fn scale_row_scalar<S: Pixel, D: Pixel>(
+  src: &[S],
+  dst: &mut [D],
+  scaling: Scaling,
+  peak: Option<f32>,
+) {
+  for (src_pixel, dst_pixel) in zip(src, dst) {
+    let value = scaling.apply(src_pixel.to_f32());
+    *dst_pixel = D::from_f32(peak.map_or(value, |peak| value.round().clamp(0.0, peak)));
+  }
+}
+
+/// Builder of [`ScaleParams`]. Unset ranges, `scale_offsets` and `chroma`
+/// default as in [`scale_value`].
+#[derive(Clone, Copy, Debug)]
+#[must_use]
+pub struct ScaleParamsBuilder<'a, U, V> {
+  format_in: &'a U,
+  format_out: &'a V,
+  range_in: Option<ColorRange>,
+  range_out: Option<ColorRange>,
+  scale_offsets: Option<bool>,
+  chroma: Option<bool>,
+}
+
+impl<'a, U, V> ScaleParamsBuilder<'a, U, V>
+where
+  U: HoldsVideoFormat,
+  V: HoldsVideoFormat,
+{
+  const fn new(format_in: &'a U, format_out: &'a V) -> Self {
+    Self {
+      format_in,
+      format_out,
+      range_in: None,
+      range_out: None,
+      scale_offsets: None,
+      chroma: None,
+    }
+  }
+
+  /// Sets the range of the input values.
+  pub const fn range_in(mut self, range_in: ColorRange) -> Self {
+    self.range_in = Some(range_in);
+    self
+  }
+
+  /// Sets the range of the output values.
+  pub const fn range_out(mut self, range_out: ColorRange) -> Self {
+    self.range_out = Some(range_out);
+    self
+  }
+
+  /// Sets whether the limited range and chroma offsets are scaled.
+  pub const fn scale_offsets(mut self, scale_offsets: bool) -> Self {
+    self.scale_offsets = Some(scale_offsets);
+    self
+  }
+
+  /// Sets whether the values are chroma values. Ignored for RGB formats.
+  pub const fn chroma(mut self, chroma: bool) -> Self {
+    self.chroma = Some(chroma);
+    self
+  }
+
+  /// Resolves the defaults and computes the transform.
+  #[must_use]
+  pub fn build(self) -> ScaleParams {
+    ScaleParams {
+      scaling: Scaling::new(
+        self.format_in,
+        self.format_out,
+        self.range_in,
+        self.range_out,
+        self.scale_offsets,
+        self.chroma,
+      ),
+      integer_in: self.format_in.sample_type() == SampleType::Integer,
+      peak_in: self.format_in.peak_value(None, Some(ColorRange::Full)),
+      integer_out: self.format_out.sample_type() == SampleType::Integer,
+      peak_out: self.format_out.peak_value(None, Some(ColorRange::Full)),
+    }
+  }
+}
+
+/// A conversion between two bit depths, as done by [`scale_value`] but with
+/// its defaults resolved and its transform computed once.
+///
+/// ```
+/// use vapours::{
+///   enums::ColorRange,
+///   scale::ScaleParams,
+///   vs_enums::{GRAY10, GRAY8},
+/// };
+///
+/// let params = ScaleParams::builder(&GRAY8, &GRAY10)
+///   .range_in(ColorRange::Full)
+///   .build();
+/// assert_eq!(params.apply(255.0), 940.0);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScaleParams {
+  scaling: Option<Scaling>,
+  integer_in: bool,
+  peak_in: f32,
+  integer_out: bool,
+  peak_out: f32,
+}
+
+impl ScaleParams {
+  /// Starts describing a conversion from `format_in` to `format_out`.
+  pub const fn builder<'a, U, V>(
+    format_in: &'a U,
+    format_out: &'a V,
+  ) -> ScaleParamsBuilder<'a, U, V>
+  where
+    U: HoldsVideoFormat,
+    V: HoldsVideoFormat,
   {
-    return out_value;
+    ScaleParamsBuilder::new(format_in, format_out)
   }
 
-  let chroma = if is_input_rgb || is_output_rgb {
-    false
-  } else {
-    chroma.unwrap_or(false)
-  };
+  /// Scales a value to the output format, with the same result as
+  /// [`scale_value`]. Values are rounded and clamped for integer output.
+  #[inline]
+  #[must_use]
+  pub fn apply(&self, value: f32) -> f32 {
+    let Some(scaling) = self.scaling else {
+      return value;
+    };
+
+    let out_value = scaling.apply(value);
+    if self.integer_out {
+      return out_value.round().clamp(0.0, self.peak_out);
+    }
 
-  let input_peak = format_in.peak_value(Some(chroma), Some(range_in));
-  let input_lowest = format_in.lowest_value(Some(chroma), Some(range_in));
-  let output_peak = format_out.peak_value(Some(chroma), Some(range_out));
-  let output_lowest = format_out.lowest_value(Some(chroma), Some(range_out));
+    out_value
+  }
 
-  if scale_offsets && format_in.sample_type() == SampleType::Integer {
-    if chroma {
-      out_value -= (128 << (format_in.depth() - 8)) as f32;
-    } else if range_in == ColorRange::Limited {
-      out_value -= (16 << (format_in.depth() - 8)) as f32;
+  /// Scales a value of the output format back to the input format. Values are
+  /// rounded and clamped for integer input.
+  #[inline]
+  #[must_use]
+  pub fn apply_inverse(&self, value: f32) -> f32 {
+    let Some(scaling) = self.scaling else {
+      return value;
+    };
+
+    let in_value = (value - scaling.offset_out) / scaling.gain + scaling.offset_in;
+    if self.integer_in {
+      return in_value.round().clamp(0.0, self.peak_in);
     }
+
+    in_value
   }
 
-  out_value *= (output_peak - output_lowest) / (input_peak - input_lowest);
+  /// Scales a value to the output format without rounding or clamping.
+  #[inline]
+  pub(crate) fn apply_unrounded(&self, value: f32) -> f32 {
+    self.scaling.map_or(value, |scaling| scaling.apply(value))
+  }
 
-  if scale_offsets && format_out.sample_type() == SampleType::Integer {
-    if chroma {
-      out_value += (128 << (format_out.depth() - 8)) as f32;
-    } else if range_out == ColorRange::Limited {
-      out_value += (16 << (format_out.depth() - 8)) as f32;
+  /// Peak value of an integer output format, or [`None`] for float output.
+  pub(crate) const fn integer_peak_out(&self) -> Option<f32> {
+    if self.integer_out {
+      Some(self.peak_out)
+    } else {
+      None
     }
   }
 
-  if format_out.sample_type() == SampleType::Integer {
-    out_value = out_value
-      .round()
-      .clamp(0.0, format_out.peak_value(None, Some(ColorRange::Full)));
+  /// Multiplier of the transform, e.g. for expressions. Values are scaled as
+  /// `(value - offset_in) * gain + offset_out`.
+  #[must_use]
+  pub fn gain(&self) -> f32 {
+    self.scaling.map_or(1.0, |scaling| scaling.gain)
+  }
+
+  /// Offset subtracted from input values.
+  #[must_use]
+  pub fn offset_in(&self) -> f32 {
+    self.scaling.map_or(0.0, |scaling| scaling.offset_in)
+  }
+
+  /// Offset added to scaled values.
+  #[must_use]
+  pub fn offset_out(&self) -> f32 {
+    self.scaling.map_or(0.0, |scaling| scaling.offset_out)
+  }
+}
+
+/// The linear transform applied by [`scale_value`], before rounding and
+/// clamping.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Scaling {
+  /// Subtracted from the input value.
+  pub offset_in: f32,
+
+  /// Multiplies the input value once its offset is removed.
+  pub gain: f32,
+
+  /// Added to the scaled value.
+  pub offset_out: f32,
+}
+
+impl Scaling {
+  /// Leaves values unchanged.
+  pub(crate) const IDENTITY: Self = Self {
+    offset_in: 0.0,
+    gain: 1.0,
+    offset_out: 0.0,
+  };
+
+  /// Resolves the transform between two formats, with the same defaults as
+  /// [`scale_value`]. Returns [`None`] if values are left unchanged.
+  #[allow(clippy::cast_precision_loss)]
+  pub(crate) fn new<U, V>(
+    format_in: &U,
+    format_out: &V,
+    range_in: Option<ColorRange>,
+    range_out: Option<ColorRange>,
+    scale_offsets: Option<bool>,
+    chroma: Option<bool>,
+  ) -> Option<Self>
+  where
+    U: HoldsVideoFormat,
+    V: HoldsVideoFormat,
+  {
+    let scale_offsets = scale_offsets.unwrap_or(true);
+
+    let is_input_rgb = format_in.color_family() == ColorFamily::RGB;
+    let is_output_rgb = format_out.color_family() == ColorFamily::RGB;
+
+    let range_in = range_in.unwrap_or(if is_input_rgb {
+      ColorRange::Full
+    } else {
+      ColorRange::Limited
+    });
+    let range_out = range_out.unwrap_or(if is_output_rgb {
+      ColorRange::Full
+    } else {
+      ColorRange::Limited
+    });
+
+    if format_in.depth() == format_out.depth()
+      && range_in == range_out
+      && format_in.sample_type() == format_out.sample_type()
+    {
+      return None;
+    }
+
+    let chroma = if is_input_rgb || is_output_rgb {
+      false
+    } else {
+      chroma.unwrap_or(false)
+    };
+
+    let input_peak = format_in.peak_value(Some(chroma), Some(range_in));
+    let input_lowest = format_in.lowest_value(Some(chroma), Some(range_in));
+    let output_peak = format_out.peak_value(Some(chroma), Some(range_out));
+    let output_lowest = format_out.lowest_value(Some(chroma), Some(range_out));
+
+    let mut offset_in = 0.0;
+    if scale_offsets && format_in.sample_type() == SampleType::Integer {
+      if chroma {
+        offset_in = (128 << (format_in.depth() - 8)) as f32;
+      } else if range_in == ColorRange::Limited {
+        offset_in = (16 << (format_in.depth() - 8)) as f32;
+      }
+    }
+
+    let gain = (output_peak - output_lowest) / (input_peak - input_lowest);
+
+    let mut offset_out = 0.0;
+    if scale_offsets && format_out.sample_type() == SampleType::Integer {
+      if chroma {
+        offset_out = (128 << (format_out.depth() - 8)) as f32;
+      } else if range_out == ColorRange::Limited {
+        offset_out = (16 << (format_out.depth() - 8)) as f32;
+      }
+    }
+
+    Some(Self {
+      offset_in,
+      gain,
+      offset_out,
+    })
   }
 
-  out_value
+  /// Applies the transform to a value.
+  #[inline]
+  #[allow(clippy::suboptimal_flops)] // `mul_add` is slow without FMA.
+  pub(crate) fn apply(self, value: f32) -> f32 {
+    (value - self.offset_in) * self.gain + self.offset_out
+  }
 }
 
 #[cfg(test)]
@@ -93,7 +400,7 @@ mod tests {
   use approx::assert_relative_eq;
   use rstest::rstest;
 
-  use crate::vs_enums::{GRAY10, GRAY8, YUV444PS};
+  use crate::vs_enums::{GRAY10, GRAY16, GRAY8, GRAYS, RGB24, YUV444P8, YUV444PS};
 
   use super::*;
 
@@ -196,4 +503,161 @@ mod tests {
       expected
     );
   }
+
+  /// Checks `scale_plane` against `scale_value` on every pixel of a plane
+  /// whose width is not a multiple of the SIMD lane count.
+  fn assert_scale_plane_parity<S, D, U, V>(
+    src_data: &[S],
+    format_in: &U,
+    format_out: &V,
+    range_in: Option<ColorRange>,
+    range_out: Option<ColorRange>,
+    chroma: Option<bool>,
+  ) where
+    S: Pixel + ToPrimitive,
+    D: Pixel,
+    U: HoldsVideoFormat,
+    V: HoldsVideoFormat,
+  {
+    let width = 37;
+    let height = src_data.len() / width;
+    let src = PlaneView {
+      data: src_data,
+      height: height as i32,
+      width: width as i32,
+      stride: (width * size_of::<S>()) as isize,
+    };
+    let mut dst_data = vec![D::default(); width * height];
+    let mut dst = PlaneViewMut {
+      data: &mut dst_data,
+      height: height as i32,
+      width: width as i32,
+      stride: (width * size_of::<D>()) as isize,
+    };
+
+    let mut params = ScaleParams::builder(format_in, format_out);
+    if let Some(range_in) = range_in {
+      params = params.range_in(range_in);
+    }
+    if let Some(range_out) = range_out {
+      params = params.range_out(range_out);
+    }
+    if let Some(chroma) = chroma {
+      params = params.chroma(chroma);
+    }
+    scale_plane(&src, &mut dst, &params.build());
+    for (&input, &output) in zip(&src_data[..width * height], &dst_data) {
+      let expected = scale_value(
+        input, format_in, format_out, range_in, range_out, None, chroma,
+      );
+      assert_eq!(output.to_f32().to_bits(), expected.to_bits());
+    }
+  }
+
+  #[test]
+  fn test_scale_plane_integer() {
+    let data: Vec<u8> = (0..=255).cycle().take(37 * 8).collect();
+    assert_scale_plane_parity::<_, u16, _, _>(&data, &GRAY8, &GRAY10, None, None, None);
+    assert_scale_plane_parity::<_, u8, _, _>(&data, &GRAY8, &GRAY8, None, None, None);
+    assert_scale_plane_parity::<_, u8, _, _>(
+      &data,
+      &GRAY8,
+      &GRAY8,
+      Some(ColorRange::Limited),
+      Some(ColorRange::Full),
+      None,
+    );
+    assert_scale_plane_parity::<_, u16, _, _>(&data, &RGB24, &GRAY16, None, None, None);
+
+    let data: Vec<u16> = (0..37 * 30).map(|i| i * 59).collect();
+    assert_scale_plane_parity::<_, u8, _, _>(&data, &GRAY16, &GRAY8, None, None, None);
+    assert_scale_plane_parity::<_, u8, _, _>(&data, &GRAY16, &YUV444P8, None, None, Some(true));
+  }
+
+  #[test]
+  fn test_scale_plane_float() {
+    let data: Vec<u8> = (0..=255).cycle().take(37 * 8).collect();
+    assert_scale_plane_parity::<_, f32, _, _>(&data, &GRAY8, &GRAYS, None, None, None);
+    assert_scale_plane_parity::<_, f32, _, _>(&data, &YUV444P8, &YUV444PS, None, None, Some(true));
+
+    let data: Vec<f32> = (0..37 * 4u16).map(|i| f32::from(i) / 100.0 - 0.2).collect();
+    assert_scale_plane_parity::<_, u8, _, _>(&data, &GRAYS, &GRAY8, None, None, None);
+    assert_scale_plane_parity::<_, f32, _, _>(&data, &GRAYS, &GRAYS, None, None, None);
+  }
+
+  #[rstest]
+  #[case(0.0)]
+  #[case(24.0)]
+  #[case(64.0)]
+  #[case(255.0)]
+  fn test_scale_params_matches_scale_value(#[case] input: f32) {
+    let params = ScaleParams::builder(&GRAY8, &YUV444PS).build();
+    assert_relative_eq!(
+      params.apply(input),
+      scale_value(input, &GRAY8, &YUV444PS, None, None, None, None)
+    );
+
+    let params = ScaleParams::builder(&GRAY8, &GRAY8)
+      .range_in(ColorRange::Limited)
+      .range_out(ColorRange::Full)
+      .build();
+    assert_relative_eq!(
+      params.apply(input),
+      scale_value(
+        input,
+        &GRAY8,
+        &GRAY8,
+        Some(ColorRange::Limited),
+        Some(ColorRange::Full),
+        None,
+        None
+      )
+    );
+
+    let params = ScaleParams::builder(&YUV444P8, &GRAY16)
+      .chroma(true)
+      .scale_offsets(false)
+      .build();
+    assert_relative_eq!(
+      params.apply(input),
+      scale_value(
+        input,
+        &YUV444P8,
+        &GRAY16,
+        None,
+        None,
+        Some(false),
+        Some(true)
+      )
+    );
+  }
+
+  #[test]
+  fn test_scale_params_inverse() {
+    let params = ScaleParams::builder(&GRAY8, &GRAY10).build();
+    for value in 0..=255u8 {
+      let value = f32::from(value);
+      assert_relative_eq!(params.apply_inverse(params.apply(value)), value);
+    }
+    assert_relative_eq!(params.apply_inverse(1023.0), 255.0);
+
+    let params = ScaleParams::builder(&GRAY8, &YUV444PS).chroma(true).build();
+    assert_relative_eq!(params.apply_inverse(0.0), 128.0);
+    assert_relative_eq!(params.apply_inverse(0.5), 240.0);
+    assert_relative_eq!(params.apply_inverse(-1.0), 0.0);
+
+    let params = ScaleParams::builder(&GRAY8, &GRAY8).build();
+    assert_relative_eq!(params.apply_inverse(12.5), 12.5);
+  }
+
+  #[test]
+  fn test_scale_params_transform() {
+    let params = ScaleParams::builder(&GRAY8, &GRAYS).build();
+    assert_relative_eq!(params.offset_in(), 16.0);
+    assert_relative_eq!(params.gain(), 1.0 / 219.0);
+    assert_relative_eq!(params.offset_out(), 0.0);
+
+    let params = ScaleParams::builder(&GRAY8, &GRAY8).build();
+    assert_relative_eq!(params.gain(), 1.0);
+  }
 }
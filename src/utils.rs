@@ -1,18 +1,24 @@
+use std::ffi::CString;
+
 use const_str::cstr;
-use strum_macros::EnumString;
+use strum_macros::{AsRefStr, EnumString};
 use vapoursynth4_rs::{
   core::Core,
+  frame::VideoFormat,
   key,
-  map::{AppendMode, Value},
+  map::{AppendMode, KeyStr, Map, Value},
   node::VideoNode,
+  plugin::Plugin,
+  ColorFamily, SampleType,
 };
 
-use crate::errors::VapoursError;
+use crate::{enums::ColorRange, errors::VapoursError, generic::HoldsVideoFormat};
 
 const FMTCONV_NAMESPACE: &str = "fmtc";
+const RESIZE_NAMESPACE: &str = "resize";
 
 /// Enum for `zimg_dither_type_e` and fmtconv `dmode`.
-#[derive(Clone, Copy, Debug, EnumString, Eq, PartialEq)]
+#[derive(AsRefStr, Clone, Copy, Debug, EnumString, Eq, PartialEq)]
 #[strum(ascii_case_insensitive, serialize_all = "snake_case")]
 pub enum DitherType {
   /// Choose automatically.
@@ -60,43 +66,565 @@ pub enum DitherType {
   Quasirandom,
 }
 
+impl DitherType {
+  /// Whether this dither type is only implemented by fmtconv.
+  #[must_use]
+  pub const fn is_fmtc(self) -> bool {
+    matches!(
+      self,
+      Self::ErrorDiffusionFmtc
+        | Self::Sierra24a
+        | Self::Stucki
+        | Self::Atkinson
+        | Self::Ostromoukhov
+        | Self::Void
+        | Self::Quasirandom
+    )
+  }
+
+  /// Returns the fmtconv `dmode` for this dither type, or [`None`] if fmtconv
+  /// has no equivalent.
+  #[must_use]
+  pub const fn fmtc_dmode(self) -> Option<i64> {
+    match self {
+      Self::Ordered => Some(0),
+      Self::None => Some(1),
+      Self::Sierra24a => Some(3),
+      Self::Stucki => Some(4),
+      Self::Atkinson => Some(5),
+      Self::ErrorDiffusionFmtc => Some(6),
+      Self::Ostromoukhov => Some(7),
+      Self::Void => Some(8),
+      Self::Quasirandom => Some(9),
+      Self::Auto | Self::Random | Self::ErrorDiffusion => None,
+    }
+  }
+
+  /// Whether a conversion between the two formats and ranges should be
+  /// dithered.
+  #[must_use]
+  pub fn should_dither<T, U>(
+    format_in: &T,
+    format_out: &U,
+    range_in: ColorRange,
+    range_out: ColorRange,
+  ) -> bool
+  where
+    T: HoldsVideoFormat,
+    U: HoldsVideoFormat,
+  {
+    if format_out.sample_type() == SampleType::Float {
+      return false;
+    }
+
+    if format_in.sample_type() == SampleType::Float || range_in != range_out {
+      return true;
+    }
+
+    let (bits_in, bits_out) = (format_in.depth(), format_out.depth());
+    if bits_in == bits_out {
+      return false;
+    }
+
+    if bits_in > bits_out {
+      return true;
+    }
+
+    range_in == ColorRange::Full && (bits_in, bits_out) != (8, 16)
+  }
+
+  /// Replaces [`DitherType::Auto`] by [`DitherType::ErrorDiffusion`] if the
+  /// conversion [should be dithered](Self::should_dither), and by
+  /// [`DitherType::None`] otherwise. Other dither types are returned as is.
+  ///
+  /// fmtconv has its own error diffusion instead, see
+  /// [`DitherType::resolve_fmtc`].
+  #[must_use]
+  pub fn resolve<T, U>(
+    self,
+    format_in: &T,
+    format_out: &U,
+    range_in: ColorRange,
+    range_out: ColorRange,
+  ) -> Self
+  where
+    T: HoldsVideoFormat,
+    U: HoldsVideoFormat,
+  {
+    match self {
+      Self::Auto if Self::should_dither(format_in, format_out, range_in, range_out) => {
+        Self::ErrorDiffusion
+      }
+      Self::Auto => Self::None,
+      other => other,
+    }
+  }
+
+  /// Like [`DitherType::resolve`], but replaces [`DitherType::Auto`] by
+  /// [`DitherType::ErrorDiffusionFmtc`] when dithering, as fmtconv does not
+  /// implement [`DitherType::ErrorDiffusion`].
+  #[must_use]
+  pub fn resolve_fmtc<T, U>(
+    self,
+    format_in: &T,
+    format_out: &U,
+    range_in: ColorRange,
+    range_out: ColorRange,
+  ) -> Self
+  where
+    T: HoldsVideoFormat,
+    U: HoldsVideoFormat,
+  {
+    match self.resolve(format_in, format_out, range_in, range_out) {
+      Self::ErrorDiffusion if self == Self::Auto => Self::ErrorDiffusionFmtc,
+      other => other,
+    }
+  }
+}
+
 /// [`Core`] extensions.
 pub trait VapoursCore {
   /// Bit depth conversion.
   ///
+  /// `sample_type` defaults to [`SampleType::Float`] for a bit depth of 32 and
+  /// [`SampleType::Integer`] otherwise. Ranges default to full for RGB and
+  /// limited for everything else. [`DitherType::Auto`] dithers with
+  /// [`DitherType::ErrorDiffusion`], or [`DitherType::ErrorDiffusionFmtc`]
+  /// with fmtconv, only when the conversion loses precision.
+  ///
+  /// The conversion is done with zimg (`resize.Point`) unless the dither type
+  /// is only implemented by fmtconv or zimg is not loaded, in which case
+  /// `fmtc.bitdepth` is used.
+  ///
+  /// There is no native fallback at the clip level: vapoursynth4-rs cannot
+  /// create a filter outside of a plugin's create function. Filters that must
+  /// not depend on either plugin can convert their frames in `get_frame` with
+  /// [`convert_frame`](crate::dither::convert_frame), which follows the same
+  /// defaults.
+  ///
   /// # Errors
   ///
-  /// Returns an error if the fmtconv plugin is not found or on any error
-  /// accessing frame properties.
-  fn depth(&self, clip: VideoNode, bit_depth: u32) -> Result<VideoNode, VapoursError>;
+  /// Returns [`VapoursError::DependencyNotFoundError`] if no plugin able to
+  /// perform the conversion is loaded. Also returns an error if the chosen
+  /// plugin does not support the requested dither type, if the output format
+  /// is invalid, or if the plugin invocation fails.
+  fn depth(
+    &self,
+    clip: VideoNode,
+    bit_depth: u32,
+    sample_type: Option<SampleType>,
+    dither_type: DitherType,
+    range_in: Option<ColorRange>,
+    range_out: Option<ColorRange>,
+  ) -> Result<VideoNode, VapoursError>;
 }
 
 impl VapoursCore for Core {
-  #[allow(unreachable_code)]
-  #[allow(unused_variables)]
-  fn depth(&self, clip: VideoNode, bit_depth: u32) -> Result<VideoNode, VapoursError> {
-    todo!("Needs configurable dither type, non-fmtc dithering, and probably more.");
+  fn depth(
+    &self,
+    clip: VideoNode,
+    bit_depth: u32,
+    sample_type: Option<SampleType>,
+    dither_type: DitherType,
+    range_in: Option<ColorRange>,
+    range_out: Option<ColorRange>,
+  ) -> Result<VideoNode, VapoursError> {
+    let format_in = clip.video_format().clone();
+    let bit_depth = i32::try_from(bit_depth)
+      .map_err(|_| VapoursError::UnsupportedFormatError(format!("{bit_depth} bits per sample")))?;
+    let sample_type = sample_type.unwrap_or(if bit_depth == 32 {
+      SampleType::Float
+    } else {
+      SampleType::Integer
+    });
+
+    let id_out = self.query_video_format_id(
+      format_in.color_family,
+      sample_type,
+      bit_depth,
+      format_in.sub_sampling_w,
+      format_in.sub_sampling_h,
+    );
+    if id_out == 0 {
+      return Err(VapoursError::UnsupportedFormatError(format!(
+        "{sample_type:?} {bit_depth} bits per sample"
+      )));
+    }
+    let format_out = self.get_video_format_by_id(id_out);
 
-    let Some(fmtc_plugin) = self.get_plugin_by_id(cstr!(FMTCONV_NAMESPACE)) else {
-      return Err(VapoursError::DependencyNotFoundError(
+    let default_range = if format_in.color_family == ColorFamily::RGB {
+      ColorRange::Full
+    } else {
+      ColorRange::Limited
+    };
+    let range_in = range_in.unwrap_or(default_range);
+    let range_out = range_out.unwrap_or(default_range);
+
+    if format_in == format_out && range_in == range_out {
+      return Ok(clip);
+    }
+
+    let resize_plugin = self.get_plugin_by_namespace(cstr!(RESIZE_NAMESPACE));
+    let fmtc_plugin = self.get_plugin_by_namespace(cstr!(FMTCONV_NAMESPACE));
+    let (backend, plugin) = DepthBackend::select(dither_type, resize_plugin, fmtc_plugin)?;
+
+    let mut args = self.create_map();
+    set_arg(&mut args, key!(c"clip"), Value::VideoNode(clip))?;
+
+    let function = match backend {
+      DepthBackend::Resize => {
+        let dither_type = dither_type.resolve(&format_in, &format_out, range_in, range_out);
+        ResizeArgs::new(id_out, dither_type, range_in, range_out).set(&mut args)?;
+        "Point"
+      }
+      DepthBackend::Fmtc => {
+        let dither_type = dither_type.resolve_fmtc(&format_in, &format_out, range_in, range_out);
+        FmtcArgs::new(&format_in, &format_out, dither_type, range_in, range_out)?.set(&mut args)?;
+        "bitdepth"
+      }
+    };
+
+    invoke(&plugin, function, &args)?
+      .get_video_node(key!(c"clip"), 0)
+      .map_err(|e| VapoursError::FramePropertyError("clip".to_string(), e.into()))
+  }
+}
+
+/// Plugin performing a depth conversion.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum DepthBackend {
+  /// zimg, through `resize.Point`.
+  Resize,
+
+  /// fmtconv, through `fmtc.bitdepth`.
+  Fmtc,
+}
+
+impl DepthBackend {
+  /// Picks zimg unless the dither type is only implemented by fmtconv or zimg
+  /// is not loaded, and returns it along with its plugin.
+  fn select<P>(
+    dither_type: DitherType,
+    resize_plugin: Option<P>,
+    fmtc_plugin: Option<P>,
+  ) -> Result<(Self, P), VapoursError> {
+    match (resize_plugin, fmtc_plugin) {
+      (Some(plugin), _) if !dither_type.is_fmtc() => Ok((Self::Resize, plugin)),
+      (_, Some(plugin)) => Ok((Self::Fmtc, plugin)),
+      (None, None) if !dither_type.is_fmtc() => Err(VapoursError::DependencyNotFoundError(
+        RESIZE_NAMESPACE.to_string(),
+      )),
+      _ => Err(VapoursError::DependencyNotFoundError(
+        FMTCONV_NAMESPACE.to_string(),
+      )),
+    }
+  }
+}
+
+/// Arguments of `resize.Point` for a depth conversion.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct ResizeArgs {
+  format: i64,
+  dither_type: DitherType,
+  range_in: i64,
+  range: i64,
+}
+
+impl ResizeArgs {
+  fn new(
+    id_out: u32,
+    dither_type: DitherType,
+    range_in: ColorRange,
+    range_out: ColorRange,
+  ) -> Self {
+    Self {
+      format: i64::from(id_out),
+      dither_type,
+      range_in: range_in.value_zimg(),
+      range: range_out.value_zimg(),
+    }
+  }
+
+  fn set(self, args: &mut Map) -> Result<(), VapoursError> {
+    set_arg(args, key!(c"format"), Value::Int(self.format))?;
+    set_arg(
+      args,
+      key!(c"dither_type"),
+      Value::Utf8(self.dither_type.as_ref()),
+    )?;
+    set_arg(args, key!(c"range_in"), Value::Int(self.range_in))?;
+    set_arg(args, key!(c"range"), Value::Int(self.range))
+  }
+}
+
+/// Arguments of `fmtc.bitdepth` for a depth conversion.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct FmtcArgs {
+  bits: i64,
+  flt: i64,
+  fulls: i64,
+  fulld: i64,
+  dmode: i64,
+}
+
+impl FmtcArgs {
+  fn new(
+    format_in: &VideoFormat,
+    format_out: &VideoFormat,
+    dither_type: DitherType,
+    range_in: ColorRange,
+    range_out: ColorRange,
+  ) -> Result<Self, VapoursError> {
+    // Ostromoukhov error diffusion is only available for integer input.
+    let dmode = dither_type.fmtc_dmode().filter(|_| {
+      dither_type != DitherType::Ostromoukhov || format_in.sample_type == SampleType::Integer
+    });
+    let Some(dmode) = dmode else {
+      return Err(VapoursError::UnsupportedDitherError(
+        dither_type,
         FMTCONV_NAMESPACE.to_string(),
       ));
     };
 
-    let mut args = self.create_map();
-    args
-      .set(key!(c"clip"), Value::VideoNode(clip), AppendMode::Replace)
-      .map_err(|_| VapoursError::FramePropertyError("clip".to_string()))?;
-    args
-      .set(
-        key!(c"bitdepth"),
-        Value::Int(i64::from(bit_depth)),
-        AppendMode::Replace,
+    Ok(Self {
+      bits: i64::from(format_out.bits_per_sample),
+      flt: i64::from(format_out.sample_type == SampleType::Float),
+      fulls: i64::from(range_in == ColorRange::Full),
+      fulld: i64::from(range_out == ColorRange::Full),
+      dmode,
+    })
+  }
+
+  fn set(self, args: &mut Map) -> Result<(), VapoursError> {
+    set_arg(args, key!(c"bits"), Value::Int(self.bits))?;
+    set_arg(args, key!(c"flt"), Value::Int(self.flt))?;
+    set_arg(args, key!(c"fulls"), Value::Int(self.fulls))?;
+    set_arg(args, key!(c"fulld"), Value::Int(self.fulld))?;
+    set_arg(args, key!(c"dmode"), Value::Int(self.dmode))
+  }
+}
+
+/// Sets a single argument of a plugin function call.
+fn set_arg(args: &mut Map, key: &KeyStr, value: Value) -> Result<(), VapoursError> {
+  args
+    .set(key, value, AppendMode::Replace)
+    .map_err(|e| VapoursError::FramePropertyError(key.to_string(), e.into()))
+}
+
+/// Invokes a plugin function, turning an error set on the returned map into a
+/// [`VapoursError`].
+fn invoke(plugin: &Plugin, function: &str, args: &Map) -> Result<Map, VapoursError> {
+  let name = CString::new(function).expect("function name should not contain NUL bytes");
+  let ret = plugin.invoke(&name, args);
+  if let Some(error) = ret.get_error() {
+    return Err(VapoursError::InvocationError(
+      format!("{}.{function}", plugin.namespace().to_string_lossy()),
+      error.to_string_lossy().into_owned(),
+    ));
+  }
+
+  Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::str::FromStr;
+
+  use rstest::rstest;
+  use vapoursynth4_rs::frame::VideoFormat;
+
+  use crate::vs_enums::{
+    video_format_id, GRAY10, GRAY16, GRAY8, GRAYS, RGB24, RGBS, YUV420P16, YUV420P8,
+  };
+
+  use super::*;
+
+  #[rstest]
+  #[case("error_diffusion", DitherType::ErrorDiffusion)]
+  #[case("sierra_2_4a", DitherType::Sierra24a)]
+  #[case("VOID", DitherType::Void)]
+  fn test_dither_type_from_str(#[case] input: &str, #[case] expected: DitherType) {
+    assert_eq!(DitherType::from_str(input), Ok(expected));
+    assert_eq!(expected.as_ref(), input.to_ascii_lowercase());
+  }
+
+  #[test]
+  fn test_fmtc_dmode() {
+    assert_eq!(DitherType::Ordered.fmtc_dmode(), Some(0));
+    assert_eq!(DitherType::None.fmtc_dmode(), Some(1));
+    assert_eq!(DitherType::Quasirandom.fmtc_dmode(), Some(9));
+    assert_eq!(DitherType::Random.fmtc_dmode(), None);
+    assert_eq!(DitherType::ErrorDiffusion.fmtc_dmode(), None);
+  }
+
+  #[rstest]
+  #[case(GRAY8, GRAY8, ColorRange::Limited, ColorRange::Limited, false)]
+  #[case(GRAY8, GRAY8, ColorRange::Full, ColorRange::Limited, true)]
+  #[case(GRAY16, GRAY8, ColorRange::Limited, ColorRange::Limited, true)]
+  #[case(GRAY8, GRAY10, ColorRange::Limited, ColorRange::Limited, false)]
+  #[case(GRAY8, GRAY10, ColorRange::Full, ColorRange::Full, true)]
+  #[case(GRAY8, GRAY16, ColorRange::Full, ColorRange::Full, false)]
+  #[case(GRAYS, GRAY16, ColorRange::Full, ColorRange::Full, true)]
+  #[case(GRAY16, GRAYS, ColorRange::Full, ColorRange::Full, false)]
+  #[case(YUV420P16, YUV420P8, ColorRange::Limited, ColorRange::Limited, true)]
+  #[case(RGB24, RGB24, ColorRange::Full, ColorRange::Full, false)]
+  fn test_should_dither(
+    #[case] format_in: VideoFormat,
+    #[case] format_out: VideoFormat,
+    #[case] range_in: ColorRange,
+    #[case] range_out: ColorRange,
+    #[case] expected: bool,
+  ) {
+    assert_eq!(
+      DitherType::should_dither(&format_in, &format_out, range_in, range_out),
+      expected
+    );
+  }
+
+  #[rstest]
+  #[case(DitherType::Auto, GRAY16, GRAY8, DitherType::ErrorDiffusion)]
+  #[case(DitherType::Auto, GRAY8, GRAY16, DitherType::None)]
+  #[case(DitherType::Ordered, GRAY8, GRAY16, DitherType::Ordered)]
+  fn test_dither_type_resolve(
+    #[case] dither_type: DitherType,
+    #[case] format_in: VideoFormat,
+    #[case] format_out: VideoFormat,
+    #[case] expected: DitherType,
+  ) {
+    assert_eq!(
+      dither_type.resolve(
+        &format_in,
+        &format_out,
+        ColorRange::Limited,
+        ColorRange::Limited
+      ),
+      expected
+    );
+  }
+
+  #[rstest]
+  #[case(DitherType::Auto, GRAY16, GRAY8, DitherType::ErrorDiffusionFmtc)]
+  #[case(DitherType::Auto, GRAY8, GRAY16, DitherType::None)]
+  #[case(DitherType::ErrorDiffusion, GRAY16, GRAY8, DitherType::ErrorDiffusion)]
+  #[case(DitherType::Void, GRAY16, GRAY8, DitherType::Void)]
+  fn test_dither_type_resolve_fmtc(
+    #[case] dither_type: DitherType,
+    #[case] format_in: VideoFormat,
+    #[case] format_out: VideoFormat,
+    #[case] expected: DitherType,
+  ) {
+    assert_eq!(
+      dither_type.resolve_fmtc(
+        &format_in,
+        &format_out,
+        ColorRange::Limited,
+        ColorRange::Limited
+      ),
+      expected
+    );
+  }
+
+  #[rstest]
+  #[case(DitherType::Auto, true, true, Ok(DepthBackend::Resize))]
+  #[case(DitherType::Auto, false, true, Ok(DepthBackend::Fmtc))]
+  #[case(DitherType::Ordered, true, false, Ok(DepthBackend::Resize))]
+  #[case(DitherType::Void, true, true, Ok(DepthBackend::Fmtc))]
+  #[case(DitherType::Auto, false, false, Err(RESIZE_NAMESPACE))]
+  #[case(DitherType::Void, true, false, Err(FMTCONV_NAMESPACE))]
+  #[case(DitherType::Void, false, false, Err(FMTCONV_NAMESPACE))]
+  fn test_depth_backend_select(
+    #[case] dither_type: DitherType,
+    #[case] has_resize: bool,
+    #[case] has_fmtc: bool,
+    #[case] expected: Result<DepthBackend, &str>,
+  ) {
+    let backend = DepthBackend::select(
+      dither_type,
+      has_resize.then_some(RESIZE_NAMESPACE),
+      has_fmtc.then_some(FMTCONV_NAMESPACE),
+    );
+    match expected {
+      Ok(DepthBackend::Resize) => assert_eq!(backend.unwrap(), (DepthBackend::Resize, "resize")),
+      Ok(DepthBackend::Fmtc) => assert_eq!(backend.unwrap(), (DepthBackend::Fmtc, "fmtc")),
+      Err(namespace) => assert!(matches!(
+        backend,
+        Err(VapoursError::DependencyNotFoundError(name)) if name == namespace
+      )),
+    }
+  }
+
+  #[test]
+  fn test_resize_args() {
+    assert_eq!(
+      ResizeArgs::new(
+        video_format_id(&GRAY8),
+        DitherType::ErrorDiffusion,
+        ColorRange::Limited,
+        ColorRange::Full
+      ),
+      ResizeArgs {
+        format: i64::from(video_format_id(&GRAY8)),
+        dither_type: DitherType::ErrorDiffusion,
+        range_in: 0,
+        range: 1,
+      }
+    );
+  }
+
+  #[test]
+  fn test_fmtc_args() {
+    let dither_type =
+      DitherType::Auto.resolve_fmtc(&GRAY16, &GRAY8, ColorRange::Limited, ColorRange::Limited);
+    assert_eq!(
+      FmtcArgs::new(
+        &GRAY16,
+        &GRAY8,
+        dither_type,
+        ColorRange::Limited,
+        ColorRange::Limited
       )
-      .map_err(|_| VapoursError::FramePropertyError("clip".to_string()))?;
-    let ret = fmtc_plugin.invoke(cstr!("bitdepth"), &args);
-    ret
-      .get_video_node(key!(c"clip"), 0)
-      .map_err(|_| VapoursError::FramePropertyError("clip".to_string()))
+      .unwrap(),
+      FmtcArgs {
+        bits: 8,
+        flt: 0,
+        fulls: 0,
+        fulld: 0,
+        dmode: 6,
+      }
+    );
+    assert_eq!(
+      FmtcArgs::new(
+        &GRAY8,
+        &GRAYS,
+        DitherType::None,
+        ColorRange::Full,
+        ColorRange::Full
+      )
+      .unwrap(),
+      FmtcArgs {
+        bits: 32,
+        flt: 1,
+        fulls: 1,
+        fulld: 1,
+        dmode: 1,
+      }
+    );
+
+    for (format_in, dither_type) in [
+      (GRAY16, DitherType::ErrorDiffusion),
+      (GRAY16, DitherType::Random),
+      (RGBS, DitherType::Ostromoukhov),
+    ] {
+      assert!(matches!(
+        FmtcArgs::new(
+          &format_in,
+          &RGB24,
+          dither_type,
+          ColorRange::Full,
+          ColorRange::Full
+        ),
+        Err(VapoursError::UnsupportedDitherError(..))
+      ));
+    }
   }
 }
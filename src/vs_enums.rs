@@ -1,9 +1,14 @@
 //! More Rust equivalents to VapourSynth enums.
 
+use std::{fmt, str::FromStr};
+
+use num_traits::FromPrimitive;
 use seq_macro::seq;
 use vapoursynth4_rs::{frame::VideoFormat, ColorFamily, SampleType};
 
-const fn make_video_format(
+use crate::{errors::VapoursError, generic::HoldsVideoFormat};
+
+pub(crate) const fn make_video_format(
   color_family: ColorFamily,
   sample_type: SampleType,
   bits_per_sample: i32,
@@ -43,6 +48,22 @@ seq!(N in 8..=32 {
 pub const GRAYH: VideoFormat = make_video_format(GRAY, FLOAT, 16, 0, 0);
 pub const GRAYS: VideoFormat = make_video_format(GRAY, FLOAT, 32, 0, 0);
 
+seq!(N in 8..=32 {
+  #[doc=concat!("YUV color family, 4:1:0 subsampling, ", N, " bits per sample.")]
+  pub const YUV410P~N: VideoFormat = make_video_format(YUV, INTEGER, N, 2, 2);
+});
+
+pub const YUV410PH: VideoFormat = make_video_format(YUV, FLOAT, 16, 2, 2);
+pub const YUV410PS: VideoFormat = make_video_format(YUV, FLOAT, 32, 2, 2);
+
+seq!(N in 8..=32 {
+  #[doc=concat!("YUV color family, 4:1:1 subsampling, ", N, " bits per sample.")]
+  pub const YUV411P~N: VideoFormat = make_video_format(YUV, INTEGER, N, 2, 0);
+});
+
+pub const YUV411PH: VideoFormat = make_video_format(YUV, FLOAT, 16, 2, 0);
+pub const YUV411PS: VideoFormat = make_video_format(YUV, FLOAT, 32, 2, 0);
+
 seq!(N in 8..=32 {
   #[doc=concat!("YUV color family, 4:2:0 subsampling, ", N, " bits per sample.")]
   pub const YUV420P~N: VideoFormat = make_video_format(YUV, INTEGER, N, 1, 1);
@@ -51,6 +72,22 @@ seq!(N in 8..=32 {
 pub const YUV420PH: VideoFormat = make_video_format(YUV, FLOAT, 16, 1, 1);
 pub const YUV420PS: VideoFormat = make_video_format(YUV, FLOAT, 32, 1, 1);
 
+seq!(N in 8..=32 {
+  #[doc=concat!("YUV color family, 4:2:2 subsampling, ", N, " bits per sample.")]
+  pub const YUV422P~N: VideoFormat = make_video_format(YUV, INTEGER, N, 1, 0);
+});
+
+pub const YUV422PH: VideoFormat = make_video_format(YUV, FLOAT, 16, 1, 0);
+pub const YUV422PS: VideoFormat = make_video_format(YUV, FLOAT, 32, 1, 0);
+
+seq!(N in 8..=32 {
+  #[doc=concat!("YUV color family, 4:4:0 subsampling, ", N, " bits per sample.")]
+  pub const YUV440P~N: VideoFormat = make_video_format(YUV, INTEGER, N, 0, 1);
+});
+
+pub const YUV440PH: VideoFormat = make_video_format(YUV, FLOAT, 16, 0, 1);
+pub const YUV440PS: VideoFormat = make_video_format(YUV, FLOAT, 32, 0, 1);
+
 seq!(N in 8..=32 {
   #[doc=concat!("YUV color family, 4:4:4 subsampling, ", N, " bits per sample.")]
   pub const YUV444P~N: VideoFormat = make_video_format(YUV, INTEGER, N, 0, 0);
@@ -60,11 +97,312 @@ pub const YUV444PH: VideoFormat = make_video_format(YUV, FLOAT, 16, 0, 0);
 pub const YUV444PS: VideoFormat = make_video_format(YUV, FLOAT, 32, 0, 0);
 
 pub const RGB24: VideoFormat = make_video_format(RGB, INTEGER, 8, 0, 0);
+pub const RGB27: VideoFormat = make_video_format(RGB, INTEGER, 9, 0, 0);
+pub const RGB30: VideoFormat = make_video_format(RGB, INTEGER, 10, 0, 0);
+pub const RGB36: VideoFormat = make_video_format(RGB, INTEGER, 12, 0, 0);
+pub const RGB42: VideoFormat = make_video_format(RGB, INTEGER, 14, 0, 0);
+pub const RGB48: VideoFormat = make_video_format(RGB, INTEGER, 16, 0, 0);
 pub const RGBH: VideoFormat = make_video_format(RGB, FLOAT, 16, 0, 0);
 pub const RGBS: VideoFormat = make_video_format(RGB, FLOAT, 32, 0, 0);
 
+/// Returns the ID of a video format, as returned by `core.query_video_format`
+/// and used by [`PresetFormat`].
+#[must_use]
+pub const fn video_format_id(format: &VideoFormat) -> u32 {
+  ((format.color_family as u32) << 28)
+    | ((format.sample_type as u32) << 24)
+    | ((format.bits_per_sample as u32) << 16)
+    | ((format.sub_sampling_w as u32) << 8)
+    | format.sub_sampling_h as u32
+}
+
+/// Returns the video format with the given ID, or [`None`] if the ID does not
+/// describe a valid format. The format is not checked against the formats
+/// supported by VapourSynth.
+#[must_use]
+pub const fn video_format_from_id(id: u32) -> Option<VideoFormat> {
+  let color_family = match id >> 28 {
+    1 => GRAY,
+    2 => RGB,
+    3 => YUV,
+    _ => return None,
+  };
+  let sample_type = match (id >> 24) & 0xf {
+    0 => INTEGER,
+    1 => FLOAT,
+    _ => return None,
+  };
+  let bits_per_sample = ((id >> 16) & 0xff) as i32;
+  let sub_sampling_w = ((id >> 8) & 0xff) as i32;
+  let sub_sampling_h = (id & 0xff) as i32;
+  if bits_per_sample < 8 || bits_per_sample > 32 || sub_sampling_w > 4 || sub_sampling_h > 4 {
+    return None;
+  }
+
+  Some(make_video_format(
+    color_family,
+    sample_type,
+    bits_per_sample,
+    sub_sampling_w,
+    sub_sampling_h,
+  ))
+}
+
+/// Whether VapourSynth accepts the format, as checked by
+/// `core.query_video_format`.
+///
+/// Valid formats have a known color family, 8 to 32 bits for integer and 16
+/// or 32 bits for float samples, and subsampling of at most 4 that is only
+/// allowed for YUV.
+#[must_use]
+pub const fn is_valid_video_format(format: &VideoFormat) -> bool {
+  let valid_depth = match format.sample_type {
+    SampleType::Integer => format.bits_per_sample >= 8 && format.bits_per_sample <= 32,
+    SampleType::Float => format.bits_per_sample == 16 || format.bits_per_sample == 32,
+  };
+  let valid_sub_sampling = match format.color_family {
+    ColorFamily::YUV => {
+      format.sub_sampling_w >= 0
+        && format.sub_sampling_w <= 4
+        && format.sub_sampling_h >= 0
+        && format.sub_sampling_h <= 4
+    }
+    ColorFamily::Gray | ColorFamily::RGB => {
+      format.sub_sampling_w == 0 && format.sub_sampling_h == 0
+    }
+    ColorFamily::Undefined => false,
+  };
+  valid_depth && valid_sub_sampling
+}
+
+/// VapourSynth's preset video formats (`vs.PresetVideoFormat`), with their
+/// numeric IDs as discriminants.
+#[derive(Clone, Copy, Debug, Eq, FromPrimitive, Hash, PartialEq)]
+#[repr(u32)]
+pub enum PresetFormat {
+  Gray8 = video_format_id(&GRAY8),
+  Gray9 = video_format_id(&GRAY9),
+  Gray10 = video_format_id(&GRAY10),
+  Gray12 = video_format_id(&GRAY12),
+  Gray14 = video_format_id(&GRAY14),
+  Gray16 = video_format_id(&GRAY16),
+  Gray32 = video_format_id(&GRAY32),
+  GrayH = video_format_id(&GRAYH),
+  GrayS = video_format_id(&GRAYS),
+
+  Yuv410P8 = video_format_id(&YUV410P8),
+  Yuv411P8 = video_format_id(&YUV411P8),
+  Yuv440P8 = video_format_id(&YUV440P8),
+  Yuv420P8 = video_format_id(&YUV420P8),
+  Yuv422P8 = video_format_id(&YUV422P8),
+  Yuv444P8 = video_format_id(&YUV444P8),
+  Yuv420P9 = video_format_id(&YUV420P9),
+  Yuv422P9 = video_format_id(&YUV422P9),
+  Yuv444P9 = video_format_id(&YUV444P9),
+  Yuv420P10 = video_format_id(&YUV420P10),
+  Yuv422P10 = video_format_id(&YUV422P10),
+  Yuv444P10 = video_format_id(&YUV444P10),
+  Yuv420P12 = video_format_id(&YUV420P12),
+  Yuv422P12 = video_format_id(&YUV422P12),
+  Yuv444P12 = video_format_id(&YUV444P12),
+  Yuv420P14 = video_format_id(&YUV420P14),
+  Yuv422P14 = video_format_id(&YUV422P14),
+  Yuv444P14 = video_format_id(&YUV444P14),
+  Yuv420P16 = video_format_id(&YUV420P16),
+  Yuv422P16 = video_format_id(&YUV422P16),
+  Yuv444P16 = video_format_id(&YUV444P16),
+  Yuv420PH = video_format_id(&YUV420PH),
+  Yuv420PS = video_format_id(&YUV420PS),
+  Yuv422PH = video_format_id(&YUV422PH),
+  Yuv422PS = video_format_id(&YUV422PS),
+  Yuv444PH = video_format_id(&YUV444PH),
+  Yuv444PS = video_format_id(&YUV444PS),
+
+  Rgb24 = video_format_id(&RGB24),
+  Rgb27 = video_format_id(&RGB27),
+  Rgb30 = video_format_id(&RGB30),
+  Rgb36 = video_format_id(&RGB36),
+  Rgb42 = video_format_id(&RGB42),
+  Rgb48 = video_format_id(&RGB48),
+  RgbH = video_format_id(&RGBH),
+  RgbS = video_format_id(&RGBS),
+}
+
+impl PresetFormat {
+  /// Returns the numeric ID of this preset.
+  #[must_use]
+  pub const fn id(self) -> u32 {
+    self as u32
+  }
+
+  /// Returns the preset with the given numeric ID, if any.
+  #[must_use]
+  pub fn from_id(id: u32) -> Option<Self> {
+    Self::from_u32(id)
+  }
+
+  /// Returns the preset matching a video format, if any.
+  #[must_use]
+  pub fn from_video_format<F: HoldsVideoFormat>(format: &F) -> Option<Self> {
+    Self::from_id(video_format_id(format.video_format()))
+  }
+
+  /// Returns the video format of this preset.
+  #[must_use]
+  pub const fn video_format(self) -> VideoFormat {
+    match video_format_from_id(self.id()) {
+      Some(format) => format,
+      None => unreachable!(),
+    }
+  }
+}
+
+impl fmt::Display for PresetFormat {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    FormatName(self.video_format()).fmt(f)
+  }
+}
+
+impl FromStr for PresetFormat {
+  type Err = VapoursError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let FormatName(format) = s.parse()?;
+    Self::from_video_format(&format)
+      .ok_or_else(|| VapoursError::UnsupportedFormatError(format!("'{s}' is not a preset format")))
+  }
+}
+
+/// A video format named as by VapourSynth, e.g. `Gray8`, `YUV420P10`,
+/// `RGB30` or `RGBS`.
+///
+/// Parsing is case-insensitive and accepts any valid format, including those
+/// without a preset such as `YUV422P11` or `YUVssw2ssh1P8`. Float formats
+/// other than 16 or 32 bits have no name and display as `Invalid`.
+///
+/// ```
+/// use vapours::vs_enums::{FormatName, RGBS, YUV420P10};
+///
+/// let FormatName(format) = "yuv420p10".parse()?;
+/// assert_eq!(format, YUV420P10);
+/// assert_eq!(FormatName(RGBS).to_string(), "RGBS");
+/// # Ok::<(), vapours::errors::VapoursError>(())
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FormatName(pub VideoFormat);
+
+impl fmt::Display for FormatName {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let format = &self.0;
+    if format.sample_type == SampleType::Float && !matches!(format.bits_per_sample, 16 | 32) {
+      return write!(f, "Invalid");
+    }
+    let sample = |f: &mut fmt::Formatter<'_>, bits: i32| match (format.sample_type, bits) {
+      (SampleType::Float, 16) => write!(f, "H"),
+      (SampleType::Float, _) => write!(f, "S"),
+      (SampleType::Integer, _) => write!(f, "{bits}"),
+    };
+
+    match format.color_family {
+      ColorFamily::Undefined => write!(f, "Undefined"),
+      ColorFamily::Gray => {
+        write!(f, "Gray")?;
+        sample(f, format.bits_per_sample)
+      }
+      ColorFamily::RGB => {
+        write!(f, "RGB")?;
+        let bits = if format.sample_type == SampleType::Integer {
+          format.bits_per_sample * 3
+        } else {
+          format.bits_per_sample
+        };
+        sample(f, bits)
+      }
+      ColorFamily::YUV => {
+        match (format.sub_sampling_w, format.sub_sampling_h) {
+          (0, 0) => write!(f, "YUV444P"),
+          (1, 0) => write!(f, "YUV422P"),
+          (1, 1) => write!(f, "YUV420P"),
+          (2, 0) => write!(f, "YUV411P"),
+          (2, 2) => write!(f, "YUV410P"),
+          (0, 1) => write!(f, "YUV440P"),
+          (w, h) => write!(f, "YUVssw{w}ssh{h}P"),
+        }?;
+        sample(f, format.bits_per_sample)
+      }
+    }
+  }
+}
+
+impl FromStr for FormatName {
+  type Err = VapoursError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    parse_format_name(&s.to_ascii_uppercase())
+      .map(Self)
+      .ok_or_else(|| VapoursError::UnsupportedFormatError(format!("unknown format name '{s}'")))
+  }
+}
+
+/// Parses an uppercase format name.
+fn parse_format_name(name: &str) -> Option<VideoFormat> {
+  let (color_family, sub_sampling, sample) = if let Some(sample) = name.strip_prefix("GRAY") {
+    (GRAY, (0, 0), sample)
+  } else if let Some(sample) = name.strip_prefix("RGB") {
+    (RGB, (0, 0), sample)
+  } else {
+    let rest = name.strip_prefix("YUV")?;
+    let (sub_sampling, rest) = match rest.get(..3) {
+      Some("444") => ((0, 0), &rest[3..]),
+      Some("422") => ((1, 0), &rest[3..]),
+      Some("420") => ((1, 1), &rest[3..]),
+      Some("411") => ((2, 0), &rest[3..]),
+      Some("410") => ((2, 2), &rest[3..]),
+      Some("440") => ((0, 1), &rest[3..]),
+      _ => {
+        let (w, rest) = rest.strip_prefix("SSW")?.split_once("SSH")?;
+        let (h, rest) = rest.split_at(rest.find('P')?);
+        ((parse_digits(w)?, parse_digits(h)?), rest)
+      }
+    };
+    (YUV, sub_sampling, rest.strip_prefix('P')?)
+  };
+
+  let (sample_type, mut bits) = match sample {
+    "H" => (FLOAT, 16),
+    "S" => (FLOAT, 32),
+    _ => (INTEGER, parse_digits(sample)?),
+  };
+  if color_family == RGB && sample_type == INTEGER {
+    if bits % 3 != 0 {
+      return None;
+    }
+    bits /= 3;
+  }
+
+  let format = make_video_format(
+    color_family,
+    sample_type,
+    bits,
+    sub_sampling.0,
+    sub_sampling.1,
+  );
+  // Fields too large for their byte of the ID would be read back differently.
+  video_format_from_id(video_format_id(&format)).filter(|parsed| *parsed == format)
+}
+
+/// Parses a number made of ASCII digits only, without a sign.
+fn parse_digits(s: &str) -> Option<i32> {
+  if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+    return None;
+  }
+  s.parse().ok()
+}
+
 #[cfg(test)]
 mod tests {
+  use rstest::rstest;
+
   use super::*;
 
   #[test]
@@ -89,4 +427,137 @@ mod tests {
     assert_eq!(YUV444PS.bits_per_sample, 32);
     assert_eq!(YUV444PS.bytes_per_sample, 4);
   }
+
+  #[test]
+  fn test_preset_format_ids() {
+    use vapoursynth4_rs::ffi::VSPresetVideoFormat;
+
+    assert_eq!(PresetFormat::Gray8.id(), VSPresetVideoFormat::Gray8 as u32);
+    assert_eq!(
+      PresetFormat::Yuv410P8.id(),
+      VSPresetVideoFormat::YUV410P8 as u32
+    );
+    assert_eq!(
+      PresetFormat::Yuv420P10.id(),
+      VSPresetVideoFormat::YUV420P10 as u32
+    );
+    assert_eq!(
+      PresetFormat::Yuv422PS.id(),
+      VSPresetVideoFormat::YUV422PS as u32
+    );
+    assert_eq!(PresetFormat::Rgb42.id(), VSPresetVideoFormat::RGB42 as u32);
+    assert_eq!(PresetFormat::RgbH.id(), VSPresetVideoFormat::RGBH as u32);
+    assert_eq!(PresetFormat::Yuv420P8.id(), 0x3008_0101);
+  }
+
+  #[test]
+  fn test_preset_format_video_format() {
+    assert_eq!(PresetFormat::Yuv440P8.video_format(), YUV440P8);
+    assert_eq!(PresetFormat::Rgb30.video_format(), RGB30);
+    assert_eq!(PresetFormat::GrayH.video_format(), GRAYH);
+
+    assert_eq!(
+      PresetFormat::from_video_format(&YUV422P16),
+      Some(PresetFormat::Yuv422P16)
+    );
+    assert_eq!(PresetFormat::from_video_format(&YUV422P11), None);
+    assert_eq!(
+      PresetFormat::from_id(0x3008_0101),
+      Some(PresetFormat::Yuv420P8)
+    );
+    assert_eq!(PresetFormat::from_id(0), None);
+  }
+
+  #[test]
+  fn test_video_format_from_id() {
+    assert_eq!(
+      video_format_from_id(video_format_id(&YUV411P13)),
+      Some(YUV411P13)
+    );
+    assert_eq!(video_format_from_id(video_format_id(&RGBS)), Some(RGBS));
+    assert_eq!(video_format_from_id(0), None);
+    assert_eq!(video_format_from_id(0x1004_0000), None);
+    assert_eq!(video_format_from_id(0x1208_0000), None);
+  }
+
+  #[rstest]
+  #[case(GRAY8, true)]
+  #[case(YUV410P8, true)]
+  #[case(RGBH, true)]
+  #[case(make_video_format(YUV, INTEGER, 8, 4, 4), true)]
+  #[case(make_video_format(GRAY, INTEGER, 7, 0, 0), false)]
+  #[case(make_video_format(GRAY, INTEGER, 40, 0, 0), false)]
+  #[case(make_video_format(GRAY, FLOAT, 8, 0, 0), false)]
+  #[case(make_video_format(GRAY, INTEGER, 8, 1, 1), false)]
+  #[case(make_video_format(RGB, INTEGER, 8, 1, 0), false)]
+  #[case(make_video_format(YUV, INTEGER, 8, 5, 0), false)]
+  #[case(make_video_format(YUV, INTEGER, 8, 0, -1), false)]
+  #[case(make_video_format(ColorFamily::Undefined, INTEGER, 8, 0, 0), false)]
+  fn test_is_valid_video_format(#[case] format: VideoFormat, #[case] expected: bool) {
+    assert_eq!(is_valid_video_format(&format), expected);
+  }
+
+  #[rstest]
+  #[case(GRAY8, "Gray8")]
+  #[case(GRAYH, "GrayH")]
+  #[case(GRAY32, "Gray32")]
+  #[case(YUV420P10, "YUV420P10")]
+  #[case(YUV422P12, "YUV422P12")]
+  #[case(YUV410P8, "YUV410P8")]
+  #[case(YUV440PS, "YUV440PS")]
+  #[case(RGB24, "RGB24")]
+  #[case(RGB30, "RGB30")]
+  #[case(RGBS, "RGBS")]
+  #[case(make_video_format(YUV, INTEGER, 8, 2, 1), "YUVssw2ssh1P8")]
+  fn test_format_name(#[case] format: VideoFormat, #[case] name: &str) {
+    assert_eq!(FormatName(format.clone()).to_string(), name);
+    assert_eq!(name.parse::<FormatName>().unwrap(), FormatName(format));
+  }
+
+  #[rstest]
+  #[case("GRAYH", GRAYH)]
+  #[case("yuv422p12", YUV422P12)]
+  #[case("Rgb48", RGB48)]
+  #[case("YUV411P11", YUV411P11)]
+  fn test_format_name_from_str(#[case] name: &str, #[case] format: VideoFormat) {
+    assert_eq!(name.parse::<FormatName>().unwrap(), FormatName(format));
+  }
+
+  #[rstest]
+  #[case("")]
+  #[case("Gray")]
+  #[case("Gray7")]
+  #[case("GrayF24")]
+  #[case("GrayF32")]
+  #[case("Gray+8")]
+  #[case("RGB+24")]
+  #[case("YUV420P-8")]
+  #[case("YUVssw-1ssh0P8")]
+  #[case("YUVssw1ssh+1P8")]
+  #[case("YUVssw0ssh257P8")]
+  #[case("Gray264")]
+  #[case("RGB25")]
+  #[case("YUV420")]
+  #[case("YUV423P8")]
+  #[case("YUVssw5ssh0P8")]
+  #[case("XYZ12")]
+  fn test_format_name_from_str_invalid(#[case] name: &str) {
+    assert!(matches!(
+      name.parse::<FormatName>(),
+      Err(VapoursError::UnsupportedFormatError(_))
+    ));
+  }
+
+  #[test]
+  fn test_format_name_invalid_float() {
+    let format = make_video_format(GRAY, FLOAT, 24, 0, 0);
+    assert_eq!(FormatName(format).to_string(), "Invalid");
+  }
+
+  #[test]
+  fn test_preset_format_name() {
+    assert_eq!(PresetFormat::Yuv420P10.to_string(), "YUV420P10");
+    assert_eq!("rgbh".parse::<PresetFormat>().unwrap(), PresetFormat::RgbH);
+    assert!("YUV420P11".parse::<PresetFormat>().is_err());
+  }
 }